@@ -0,0 +1,441 @@
+// A terminal UI for running settlement during an ops shift, built on the reqwest clients. It shows
+// open and closed settlement windows, in-flight settlements and participant positions and limits,
+// and can close a window or advance a settlement by one state.
+//
+//     cargo run --example settlement-tui --features clients-reqwest -- \
+//         http://central-ledger.example.com http://central-settlement.example.com
+//
+// Keys: tab switches between the windows and settlements panes, up/down select, c closes the
+// selected window, a advances the selected settlement, r refreshes and q quits. Closing and
+// advancing ask for confirmation with y.
+
+use std::error::Error;
+use std::io::stdout;
+use crossterm::event::{self, Event, KeyCode};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use tui::backend::{Backend, CrosstermBackend};
+use tui::layout::{Constraint, Direction, Layout};
+use tui::style::{Color, Modifier, Style};
+use tui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+use tui::{Frame, Terminal};
+use mojaloop_api::amount::to_decimal;
+use mojaloop_api::central_ledger::participants::{
+    AnyAccountType, GetDfspAccounts, GetParticipantLimits, GetParticipants, LimitType,
+};
+use mojaloop_api::clients::reqwest::{central_ledger, settlement, Error as ClientError};
+use mojaloop_api::fspiox_api::{Amount, Currency, DateTime, FspId};
+use mojaloop_api::settlement::settlement::{GetSettlements, PutSettlement, Settlement, SettlementState};
+use mojaloop_api::settlement::settlement_windows::{
+    CloseSettlementWindow, GetSettlementWindows, SettlementWindow, SettlementWindowCloseState,
+    SettlementWindowClosurePayload, SettlementWindowState,
+};
+use rust_decimal::Decimal;
+
+const REASON: &str = "Settlement TUI";
+
+// GET /v2/settlements takes a single state, so in-flight settlements take a request per state
+const IN_FLIGHT: [SettlementState; 5] = [
+    SettlementState::PendingSettlement,
+    SettlementState::PsTransfersRecorded,
+    SettlementState::PsTransfersReserved,
+    SettlementState::PsTransfersCommitted,
+    SettlementState::Settling,
+];
+
+struct Hub {
+    central_ledger: central_ledger::Client,
+    settlement: settlement::Client,
+    runtime: tokio::runtime::Runtime,
+}
+
+struct PositionRow {
+    participant: FspId,
+    currency: Currency,
+    position: Option<Decimal>,
+    reserved: Option<Decimal>,
+    net_debit_cap: Option<Decimal>,
+}
+
+#[derive(Default)]
+struct Snapshot {
+    windows: Vec<SettlementWindow>,
+    settlements: Vec<Settlement>,
+    positions: Vec<PositionRow>,
+    errors: Vec<String>,
+}
+
+// A list lookup that fails is shown as empty, with its error in the status bar, so that one
+// unavailable service doesn't hide what the others report. A 404 is taken to mean that nothing
+// matched the filter.
+fn list<T>(result: Result<Option<Vec<T>>, ClientError>, errors: &mut Vec<String>) -> Vec<T> {
+    match result {
+        Ok(items) => items.unwrap_or_default(),
+        Err(ClientError::MojaloopApiError { status: http::StatusCode::NOT_FOUND, .. }) => Vec::new(),
+        Err(e) => {
+            errors.push(e.to_string());
+            Vec::new()
+        }
+    }
+}
+
+fn decimal(amount: &Amount) -> Option<Decimal> {
+    to_decimal(amount).ok()
+}
+
+impl Hub {
+    fn snapshot(&self) -> Snapshot {
+        self.runtime.block_on(async {
+            let mut errors = Vec::new();
+
+            let (open, closed) = futures::join!(
+                self.settlement.send(GetSettlementWindows::in_state(SettlementWindowState::Open)),
+                self.settlement.send(GetSettlementWindows::in_state(SettlementWindowState::Closed)),
+            );
+            let mut windows = list(open, &mut errors);
+            windows.extend(list(closed, &mut errors));
+
+            let settlements = futures::future::join_all(
+                IN_FLIGHT.iter().map(|state| self.settlement.send(GetSettlements::in_state(*state)))
+            ).await;
+            let settlements = settlements
+                .into_iter()
+                .flat_map(|result| list(result, &mut errors))
+                .collect();
+
+            let participants = list(self.central_ledger.send(GetParticipants {}).await, &mut errors);
+            let lookups = futures::future::join_all(
+                participants.iter().filter(|participant| participant.is_active).map(|participant| async move {
+                    let name = participant.name;
+                    let (accounts, limits) = futures::join!(
+                        self.central_ledger.send(GetDfspAccounts { name }),
+                        self.central_ledger.send(GetParticipantLimits { name }),
+                    );
+                    (name, accounts, limits)
+                })
+            ).await;
+            let mut positions = Vec::new();
+            for (participant, accounts, limits) in lookups {
+                let limits = list(limits, &mut errors);
+                // The hub's own accounts aren't positions, so the hub has no rows
+                for account in list(accounts, &mut errors) {
+                    if account.ledger_account_type != AnyAccountType::Position {
+                        continue;
+                    }
+                    let net_debit_cap = limits
+                        .iter()
+                        .find(|limit| limit.currency == account.currency && limit.limit.r#type == LimitType::NetDebitCap)
+                        .and_then(|limit| decimal(&limit.limit.value.amount()));
+                    positions.push(PositionRow {
+                        participant,
+                        currency: account.currency,
+                        position: decimal(&account.value),
+                        reserved: decimal(&account.reserved_value),
+                        net_debit_cap,
+                    });
+                }
+            }
+
+            Snapshot { windows, settlements, positions, errors }
+        })
+    }
+
+    fn close_window(&self, close: CloseSettlementWindow) -> Result<String, ClientError> {
+        let id = close.id;
+        self.runtime.block_on(self.settlement.send(close))?;
+        Ok(format!("Closed settlement window {}", id))
+    }
+
+    fn advance_settlement(&self, advance: PutSettlement) -> Result<String, ClientError> {
+        let id = advance.id;
+        let settlement = self.runtime.block_on(self.settlement.send(advance))?;
+        Ok(match settlement {
+            Some(settlement) => format!("Settlement {} is now {}", id, settlement.state.to_string()),
+            None => format!("Advanced settlement {}", id),
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Windows,
+    Settlements,
+}
+
+// An action awaiting confirmation
+enum Pending {
+    Close(CloseSettlementWindow),
+    Advance(PutSettlement),
+}
+
+struct App {
+    hub: Hub,
+    snapshot: Snapshot,
+    focus: Focus,
+    windows: TableState,
+    settlements: TableState,
+    pending: Option<Pending>,
+    message: String,
+}
+
+fn select(state: &mut TableState, len: usize, step: isize) {
+    if len == 0 {
+        state.select(None);
+        return;
+    }
+    let current = state.selected().unwrap_or(0) as isize;
+    state.select(Some((current + step).rem_euclid(len as isize) as usize));
+}
+
+impl App {
+    fn refresh(&mut self) {
+        self.snapshot = self.hub.snapshot();
+        // Keep the selections in range of the refreshed lists
+        select(&mut self.windows, self.snapshot.windows.len(), 0);
+        select(&mut self.settlements, self.snapshot.settlements.len(), 0);
+        self.message = match self.snapshot.errors.len() {
+            0 => "Refreshed".to_string(),
+            n => format!("{} lookup(s) failed: {}", n, self.snapshot.errors.join("; ")),
+        };
+    }
+
+    fn selected_window(&self) -> Option<&SettlementWindow> {
+        self.windows.selected().and_then(|i| self.snapshot.windows.get(i))
+    }
+
+    fn selected_settlement(&self) -> Option<&Settlement> {
+        self.settlements.selected().and_then(|i| self.snapshot.settlements.get(i))
+    }
+
+    fn request_close(&mut self) {
+        let close = match self.selected_window() {
+            Some(window) if window.state == SettlementWindowState::Open => CloseSettlementWindow {
+                id: window.settlement_window_id,
+                payload: SettlementWindowClosurePayload {
+                    state: SettlementWindowCloseState::Closed,
+                    reason: REASON.to_string(),
+                },
+            },
+            Some(_) => return self.message = "Only an open window can be closed".to_string(),
+            None => return self.message = "No window selected".to_string(),
+        };
+        self.message = format!("Close settlement window {}? y/n", close.id);
+        self.pending = Some(Pending::Close(close));
+    }
+
+    fn request_advance(&mut self) {
+        let advance = match self.selected_settlement() {
+            Some(settlement) => match PutSettlement::advance(settlement, REASON) {
+                Some(advance) => advance,
+                None => return self.message = format!("Settlement {} can't be advanced", settlement.id),
+            },
+            None => return self.message = "No settlement selected".to_string(),
+        };
+        self.message = format!("Advance settlement {}? y/n", advance.id);
+        self.pending = Some(Pending::Advance(advance));
+    }
+
+    fn confirm(&mut self, pending: Pending) {
+        let result = match pending {
+            Pending::Close(close) => self.hub.close_window(close),
+            Pending::Advance(advance) => self.hub.advance_settlement(advance),
+        };
+        match result {
+            Ok(done) => {
+                self.refresh();
+                self.message = done;
+            }
+            Err(e) => self.message = e.to_string(),
+        }
+    }
+
+    /// Returns false when the app should quit
+    fn on_key(&mut self, key: KeyCode) -> bool {
+        if let Some(pending) = self.pending.take() {
+            match key {
+                KeyCode::Char('y') => self.confirm(pending),
+                _ => self.message = "Cancelled".to_string(),
+            }
+            return true;
+        }
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Tab => self.focus = match self.focus {
+                Focus::Windows => Focus::Settlements,
+                Focus::Settlements => Focus::Windows,
+            },
+            KeyCode::Up | KeyCode::Down => {
+                let step = if key == KeyCode::Up { -1 } else { 1 };
+                match self.focus {
+                    Focus::Windows => select(&mut self.windows, self.snapshot.windows.len(), step),
+                    Focus::Settlements => select(&mut self.settlements, self.snapshot.settlements.len(), step),
+                }
+            }
+            KeyCode::Char('c') => self.request_close(),
+            KeyCode::Char('a') => self.request_advance(),
+            KeyCode::Char('r') => self.refresh(),
+            _ => {}
+        }
+        true
+    }
+}
+
+// DateTime serializes as an RFC 3339 string
+fn timestamp(datetime: &DateTime) -> String {
+    match serde_json::to_value(datetime) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => "?".to_string(),
+    }
+}
+
+fn show(amount: Option<Decimal>) -> String {
+    amount.map_or_else(|| "?".to_string(), |amount| amount.to_string())
+}
+
+fn pane<'a>(title: &'a str, focused: bool) -> Block<'a> {
+    let style = if focused { Style::default().fg(Color::Yellow) } else { Style::default() };
+    Block::default().borders(Borders::ALL).title(title).border_style(style)
+}
+
+fn header(titles: &[&'static str]) -> Row<'static> {
+    Row::new(titles.to_vec()).style(Style::default().add_modifier(Modifier::BOLD))
+}
+
+fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+            Constraint::Min(5),
+            Constraint::Length(3),
+        ].as_ref())
+        .split(f.size());
+    let highlight = Style::default().add_modifier(Modifier::REVERSED);
+
+    let windows = Table::new(app.snapshot.windows.iter().map(|window| Row::new(vec![
+        window.settlement_window_id.to_string(),
+        window.state.to_string(),
+        timestamp(&window.created_date),
+        window.content()
+            .iter()
+            .map(|content| format!("{} {}", content.ledger_account_type, content.currency_id))
+            .collect::<Vec<_>>()
+            .join(", "),
+    ])))
+        .header(header(&["ID", "State", "Created", "Content"]))
+        .block(pane("Settlement windows", app.focus == Focus::Windows))
+        .highlight_style(highlight)
+        .widths(&[Constraint::Length(8), Constraint::Length(20), Constraint::Length(26), Constraint::Min(10)]);
+    f.render_stateful_widget(windows, chunks[0], &mut app.windows);
+
+    let settlements = Table::new(app.snapshot.settlements.iter().map(|settlement| Row::new(vec![
+        settlement.id.to_string(),
+        settlement.state.to_string(),
+        timestamp(&settlement.changed_date),
+        settlement.settlement_windows
+            .iter()
+            .map(|window| window.id.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        settlement.participants.len().to_string(),
+    ])))
+        .header(header(&["ID", "State", "Changed", "Windows", "Participants"]))
+        .block(pane("In-flight settlements", app.focus == Focus::Settlements))
+        .highlight_style(highlight)
+        .widths(&[
+            Constraint::Length(8),
+            Constraint::Length(24),
+            Constraint::Length(26),
+            Constraint::Min(10),
+            Constraint::Length(12),
+        ]);
+    f.render_stateful_widget(settlements, chunks[1], &mut app.settlements);
+
+    let positions = Table::new(app.snapshot.positions.iter().map(|row| {
+        // Positive positions are owed by the participant, so the headroom is what's left of the
+        // net debit cap
+        let headroom = match (row.net_debit_cap, row.position) {
+            (Some(cap), Some(position)) => Some(cap - position),
+            _ => None,
+        };
+        let style = match headroom {
+            Some(headroom) if headroom.is_sign_negative() => Style::default().fg(Color::Red),
+            _ => Style::default(),
+        };
+        Row::new(vec![
+            Cell::from(row.participant.to_string()),
+            Cell::from(row.currency.to_string()),
+            Cell::from(show(row.position)),
+            Cell::from(show(row.reserved)),
+            Cell::from(show(row.net_debit_cap)),
+            Cell::from(show(headroom)),
+        ]).style(style)
+    }))
+        .header(header(&["Participant", "Currency", "Position", "Reserved", "Net debit cap", "Headroom"]))
+        .block(pane("Positions and limits", false))
+        .widths(&[
+            Constraint::Length(20),
+            Constraint::Length(10),
+            Constraint::Length(18),
+            Constraint::Length(18),
+            Constraint::Length(18),
+            Constraint::Length(18),
+        ]);
+    f.render_widget(positions, chunks[2]);
+
+    let status = Paragraph::new(app.message.as_str())
+        .block(Block::default().borders(Borders::ALL).title(
+            "tab: switch pane  up/down: select  c: close window  a: advance settlement  r: refresh  q: quit"
+        ));
+    f.render_widget(status, chunks[3]);
+}
+
+fn run<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<(), Box<dyn Error>> {
+    loop {
+        terminal.draw(|f| draw(f, app))?;
+        if let Event::Key(key) = event::read()? {
+            if !app.on_key(key.code) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    let (central_ledger_url, settlement_url) = match args.as_slice() {
+        [_, central_ledger_url, settlement_url] => (central_ledger_url, settlement_url),
+        _ => {
+            eprintln!("Usage: settlement-tui <central-ledger URL> <central-settlement URL>");
+            std::process::exit(2);
+        }
+    };
+    let hub = Hub {
+        central_ledger: central_ledger::Client::new(central_ledger_url)?,
+        settlement: settlement::Client::new(settlement_url)?,
+        runtime: tokio::runtime::Runtime::new()?,
+    };
+    let mut app = App {
+        hub,
+        snapshot: Snapshot::default(),
+        focus: Focus::Windows,
+        windows: TableState::default(),
+        settlements: TableState::default(),
+        pending: None,
+        message: String::new(),
+    };
+    app.refresh();
+
+    enable_raw_mode()?;
+    let mut stdout = stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    terminal.clear()?;
+    // Restore the terminal whether or not the UI failed
+    let result = run(&mut terminal, &mut app);
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
@@ -0,0 +1,20 @@
+pub mod oracles;
+
+/// A request to the account-lookup-service admin API
+///
+/// Participant registration, i.e. `POST /participants/{Type}/{ID}` and friends, is part of the
+/// account-lookup-service FSPIOP API rather than its admin API. Those requests carry FSPIOP
+/// headers and are answered asynchronously by callback, so they belong with the FSPIOP clients in
+/// fspiox-api, not here.
+pub trait AccountLookupRequest: crate::request::Request {}
+
+/// GET /health
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default)]
+pub struct GetHealth;
+
+crate::request::impl_request! {
+    GetHealth: AccountLookupRequest,
+    GET "/health";
+    body: crate::request::NoBody;
+    response: crate::health::HealthCheck;
+}
@@ -0,0 +1,114 @@
+use serde::{Serialize, Deserialize};
+use fspiox_api::Currency;
+use derive_more::{Display, FromStr};
+use crate::central_ledger::participants::{deserialize_is_active, PartyIdType};
+use crate::request::{NoBody, impl_request};
+use crate::account_lookup::AccountLookupRequest;
+
+#[cfg(feature = "typescript_types")]
+use ts_rs::TS;
+
+// The account-lookup-service returns the oracle endpoint's database ID, an integer, as a string.
+// Keep it as a string so we don't depend on that.
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, FromStr, Clone, PartialEq, Eq, Hash, Display)]
+pub struct OracleId(String);
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OracleEndpointType {
+    Url,
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OracleEndpoint {
+    /// The oracle's base URL, e.g. `http://simulator:8444/oracle`
+    pub value: String,
+    pub endpoint_type: OracleEndpointType,
+}
+
+/// The body of POST /oracles and PUT /oracles/{ID}
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OracleRequest {
+    pub oracle_id_type: PartyIdType,
+    pub endpoint: OracleEndpoint,
+    /// The currency this oracle serves. Where omitted, the oracle serves all currencies for its
+    /// party ID type that no currency-specific oracle serves.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Currency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_default: Option<bool>,
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Oracle {
+    pub oracle_id: OracleId,
+    pub oracle_id_type: PartyIdType,
+    pub endpoint: OracleEndpoint,
+    pub currency: Option<Currency>,
+    // Returned straight from a MySQL tinyint column by some versions
+    #[serde(deserialize_with = "deserialize_is_active")]
+    pub is_default: bool,
+}
+
+pub type Oracles = Vec<Oracle>;
+
+/// GET /oracles. Either filter may be omitted; an empty request lists all oracles.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GetOracles {
+    #[serde(rename = "type")]
+    pub party_id_type: Option<PartyIdType>,
+    pub currency: Option<Currency>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PostOracle {
+    pub oracle: OracleRequest,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PutOracle {
+    pub id: OracleId,
+    pub oracle: OracleRequest,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeleteOracle {
+    pub id: OracleId,
+}
+
+impl_request! {
+    GetOracles: AccountLookupRequest,
+    GET "/oracles";
+    query: |req| req;
+    body: NoBody;
+    response: Oracles;
+}
+
+impl_request! {
+    PostOracle: AccountLookupRequest,
+    POST "/oracles";
+    body: OracleRequest = |req| req.oracle.clone();
+    response: NoBody;
+}
+
+impl_request! {
+    PutOracle: AccountLookupRequest,
+    PUT "/oracles/{}", id;
+    body: OracleRequest = |req| req.oracle.clone();
+    response: NoBody;
+}
+
+impl_request! {
+    DeleteOracle: AccountLookupRequest,
+    DELETE "/oracles/{}", id;
+    body: NoBody;
+    response: NoBody;
+}
@@ -0,0 +1,107 @@
+// Amount is serialized as a decimal string, per FSPIOP, and that string is all fspiox-api exposes
+// of it. So arithmetic, validation and conversion to the JSON numbers some admin endpoints expect
+// all go via the string, as a Decimal.
+
+use fspiox_api::Amount;
+use rust_decimal::Decimal;
+
+#[derive(thiserror::Error, Debug)]
+pub enum AmountError {
+    #[error("Failed to serialize amount: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Amount serialized as {0}, expected a decimal string")]
+    NotAString(serde_json::Value),
+    #[error("Failed to parse amount as a decimal: {0}")]
+    Decimal(#[from] rust_decimal::Error),
+}
+
+/// Converts an [`Amount`] to a [`Decimal`], for arithmetic
+pub fn to_decimal(amount: &Amount) -> Result<Decimal, AmountError> {
+    match serde_json::to_value(amount)? {
+        serde_json::Value::String(s) => Ok(s.parse()?),
+        other => Err(AmountError::NotAString(other)),
+    }
+}
+
+/// Converts a [`Decimal`] to an [`Amount`]. Fails where the decimal isn't a valid FSPIOP amount,
+/// e.g. because it's negative or has too many decimal places.
+pub fn from_decimal(decimal: Decimal) -> Result<Amount, AmountError> {
+    Ok(serde_json::from_value(serde_json::Value::String(decimal.normalize().to_string()))?)
+}
+
+/// (De)serializes a [`Decimal`] as a JSON number, for `#[serde(with = "crate::amount::number")]`.
+/// Deserialization also accepts a decimal string. The number is read as written rather than
+/// through an f64, which would lose the precision of large amounts and of most fractions, so
+/// this only works with serde_json, and not within untagged enums or flattened structs.
+pub mod number {
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Serialize};
+    use serde_json::value::RawValue;
+
+    pub fn serialize<S: serde::Serializer>(decimal: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+        RawValue::from_string(decimal.to_string())
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        use serde::de::Error;
+        let raw = Box::<RawValue>::deserialize(deserializer)?;
+        let s = match serde_json::from_str::<String>(raw.get()) {
+            Ok(s) => s,
+            Err(_) => raw.get().to_string(),
+        };
+        if s.contains(&['e', 'E'][..]) {
+            Decimal::from_scientific(&s).map_err(D::Error::custom)
+        } else {
+            s.parse().map_err(D::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_and_from_decimal_exactly() {
+        for s in &["0", "1", "0.0001", "10.25", "123456789012345678.1234"] {
+            let amount: Amount = serde_json::from_value(serde_json::Value::String(s.to_string())).unwrap();
+            let decimal = to_decimal(&amount).unwrap();
+            assert_eq!(decimal.to_string(), *s);
+            assert_eq!(serde_json::to_value(from_decimal(decimal).unwrap()).unwrap(), serde_json::json!(s));
+        }
+    }
+
+    #[test]
+    fn from_decimal_drops_trailing_zeros() {
+        let amount = from_decimal("10.50".parse().unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(amount).unwrap(), serde_json::json!("10.5"));
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    struct Wrapper {
+        #[serde(with = "number")]
+        amount: Decimal,
+    }
+
+    #[test]
+    fn number_is_exact() {
+        for s in &["0", "-100", "123456789012345678.1234", "-0.0001", "10.25"] {
+            let json = format!(r#"{{"amount":{}}}"#, s);
+            let wrapper: Wrapper = serde_json::from_str(&json).unwrap();
+            assert_eq!(wrapper.amount.to_string(), *s);
+            assert_eq!(serde_json::to_string(&wrapper).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn number_accepts_strings_and_values() {
+        let wrapper: Wrapper = serde_json::from_str(r#"{"amount":"-90.5"}"#).unwrap();
+        assert_eq!(wrapper.amount, "-90.5".parse().unwrap());
+        let wrapper: Wrapper = serde_json::from_value(serde_json::json!({ "amount": 1e3 })).unwrap();
+        assert_eq!(wrapper.amount, Decimal::from(1000));
+        assert!(serde_json::from_str::<Wrapper>(r#"{"amount":"ten"}"#).is_err());
+    }
+}
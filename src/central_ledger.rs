@@ -1,2 +1,19 @@
 pub mod participants;
 pub mod settlement_models;
+pub mod callback_audit;
+pub mod transactions;
+pub mod enums;
+
+/// A request to the central-ledger admin API
+pub trait CentralLedgerRequest: crate::request::Request {}
+
+/// GET /health
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default)]
+pub struct GetHealth;
+
+crate::request::impl_request! {
+    GetHealth: CentralLedgerRequest,
+    GET "/health";
+    body: crate::request::NoBody;
+    response: crate::health::HealthCheck;
+}
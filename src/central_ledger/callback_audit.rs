@@ -0,0 +1,218 @@
+// Auditing of the callback endpoints registered for participants, to find DFSPs with stale or
+// missing endpoints. The audit itself works on the output of GetCallbackUrls for each participant;
+// probing the registered hostnames for reachability is optional and requires the clients-reqwest
+// feature.
+
+use std::convert::TryFrom;
+use fspiox_api::FspId;
+use strum::IntoEnumIterator;
+use crate::central_ledger::participants::{
+    callback_urls, CallbackEndpoint, CallbackEndpointError, CallbackHostname, Endpoint,
+    FspiopCallbackType,
+};
+
+#[derive(Debug, Clone)]
+pub struct InvalidCallback {
+    pub callback_type: FspiopCallbackType,
+    pub value: String,
+    /// Placeholders the path template for this type requires but the value doesn't contain
+    pub missing_placeholders: Vec<&'static str>,
+    pub error: CallbackEndpointError,
+}
+
+#[derive(Debug, Clone)]
+pub struct UnreachableHost {
+    pub hostname: CallbackHostname,
+    pub callback_types: Vec<FspiopCallbackType>,
+    pub error: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParticipantCallbackAudit {
+    pub name: FspId,
+    /// URL callback types with no registered endpoint
+    pub missing: Vec<FspiopCallbackType>,
+    /// Registered endpoints that don't match their type's path template
+    pub invalid: Vec<InvalidCallback>,
+    /// Endpoints that match their type's path template
+    pub valid: Vec<CallbackEndpoint>,
+    /// Hostnames that could not be reached. Empty unless the audit was probed.
+    pub unreachable: Vec<UnreachableHost>,
+}
+
+impl ParticipantCallbackAudit {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.invalid.is_empty() && self.unreachable.is_empty()
+    }
+}
+
+/// Audits the callback URLs registered for participant `name`, as returned by
+/// `GetCallbackUrls`, for completeness and conformance to the path templates. Email and other
+/// non-callback endpoints are ignored.
+pub fn audit(name: FspId, endpoints: &[Endpoint]) -> ParticipantCallbackAudit {
+    let missing = FspiopCallbackType::iter()
+        .filter(|callback_type| !callback_urls(endpoints).any(|url| url.r#type == *callback_type))
+        .collect();
+    let mut invalid = Vec::new();
+    let mut valid = Vec::new();
+    for url in callback_urls(endpoints) {
+        match CallbackEndpoint::try_from(url) {
+            Ok(endpoint) => valid.push(endpoint),
+            Err(error) => invalid.push(InvalidCallback {
+                callback_type: url.r#type,
+                value: url.value.clone(),
+                missing_placeholders: url.r#type
+                    .placeholders()
+                    .into_iter()
+                    .filter(|placeholder| !url.value.contains(placeholder))
+                    .collect(),
+                error,
+            }),
+        }
+    }
+    ParticipantCallbackAudit { name, missing, invalid, valid, unreachable: Vec::new() }
+}
+
+/// Audits each participant's callback URLs. See [`audit`].
+pub fn audit_all<'a>(
+    participants: impl IntoIterator<Item = (FspId, &'a [Endpoint])>
+) -> Vec<ParticipantCallbackAudit> {
+    participants
+        .into_iter()
+        .map(|(name, endpoints)| audit(name, endpoints))
+        .collect()
+}
+
+/// Probes each distinct hostname in the valid endpoints of `audit` with an HTTP GET, recording
+/// any that can't be reached in `audit.unreachable`. Any HTTP response, including an error
+/// status, counts as reachable; a simulator or DFSP backend is not expected to serve the
+/// hostname root. Timeouts are taken from the configuration of `http`.
+#[cfg(feature = "clients-reqwest")]
+pub async fn probe(http: &::reqwest::Client, audit: &mut ParticipantCallbackAudit) {
+    let mut hosts: Vec<(CallbackHostname, Vec<FspiopCallbackType>)> = Vec::new();
+    for endpoint in &audit.valid {
+        match hosts.iter_mut().find(|(hostname, _)| *hostname == endpoint.hostname) {
+            Some((_, callback_types)) => callback_types.push(endpoint.callback_type),
+            None => hosts.push((endpoint.hostname.clone(), vec![endpoint.callback_type])),
+        }
+    }
+    let results = futures::future::join_all(
+        hosts.into_iter().map(|(hostname, callback_types)| async move {
+            let result = http.get(hostname.as_str()).send().await;
+            (hostname, callback_types, result)
+        })
+    ).await;
+    audit.unreachable = results
+        .into_iter()
+        .filter_map(|(hostname, callback_types, result)|
+            result.err().map(|error| UnreachableHost {
+                hostname,
+                callback_types,
+                error: error.to_string(),
+            })
+        )
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::central_ledger::participants::CallbackUrl;
+
+    fn callback(callback_type: FspiopCallbackType, value: &str) -> Endpoint {
+        Endpoint::Callback(CallbackUrl { r#type: callback_type, value: value.to_string() })
+    }
+
+    // Every callback type registered against `hostname`, as GET /participants/{name}/endpoints
+    // returns them
+    fn all_callbacks(hostname: &str) -> Vec<Endpoint> {
+        FspiopCallbackType::iter()
+            .map(|callback_type| callback(
+                callback_type,
+                &format!("{}{}", hostname, callback_type.path_template()),
+            ))
+            .collect()
+    }
+
+    #[test]
+    fn complete_and_conforming_callbacks_pass() {
+        let mut endpoints = all_callbacks("http://dfsp.example.com");
+        endpoints.push(Endpoint::Other {
+            r#type: "ALARM_NOTIFICATION_URL".to_string(),
+            value: "not a url".to_string(),
+        });
+        let audit = audit("dfspa".parse().unwrap(), &endpoints);
+        assert!(audit.is_ok());
+        assert_eq!(audit.valid.len(), FspiopCallbackType::iter().count());
+        assert!(audit.valid.iter().all(|endpoint| endpoint.hostname.as_str() == "http://dfsp.example.com"));
+    }
+
+    #[test]
+    fn missing_callbacks_are_reported() {
+        let endpoints: Vec<Endpoint> = all_callbacks("http://dfsp.example.com")
+            .into_iter()
+            .filter(|endpoint| !matches!(
+                endpoint,
+                Endpoint::Callback(url) if url.r#type == FspiopCallbackType::FspiopCallbackUrlQuotes
+            ))
+            .collect();
+        let audit = audit("dfspa".parse().unwrap(), &endpoints);
+        assert!(!audit.is_ok());
+        assert_eq!(audit.missing, vec![FspiopCallbackType::FspiopCallbackUrlQuotes]);
+        assert!(audit.invalid.is_empty());
+
+    }
+
+    #[test]
+    fn a_participant_without_endpoints_is_missing_every_callback() {
+        let audit = audit("dfspb".parse().unwrap(), &[]);
+        assert_eq!(audit.missing.len(), FspiopCallbackType::iter().count());
+        assert!(audit.valid.is_empty());
+    }
+
+    #[test]
+    fn non_conforming_callbacks_are_reported_with_their_missing_placeholders() {
+        let mut endpoints = all_callbacks("http://dfsp.example.com");
+        for endpoint in endpoints.iter_mut() {
+            if let Endpoint::Callback(url) = endpoint {
+                match url.r#type {
+                    FspiopCallbackType::FspiopCallbackUrlTransferError =>
+                        url.value = "http://dfsp.example.com/transfers/{{id}}/error".to_string(),
+                    FspiopCallbackType::FspiopCallbackUrlTransferPost =>
+                        url.value = "dfsp.example.com/transfers".to_string(),
+                    _ => {},
+                }
+            }
+        }
+        let audit = audit("dfspa".parse().unwrap(), &endpoints);
+        assert!(!audit.is_ok());
+        assert!(audit.missing.is_empty());
+        assert_eq!(audit.valid.len(), FspiopCallbackType::iter().count() - 2);
+        assert_eq!(audit.invalid.len(), 2);
+
+        let transfer_error = audit.invalid.iter()
+            .find(|invalid| invalid.callback_type == FspiopCallbackType::FspiopCallbackUrlTransferError)
+            .unwrap();
+        assert_eq!(transfer_error.missing_placeholders, vec!["{{transferId}}"]);
+        assert!(matches!(transfer_error.error, CallbackEndpointError::TemplateMismatch { .. }));
+
+        let transfer_post = audit.invalid.iter()
+            .find(|invalid| invalid.callback_type == FspiopCallbackType::FspiopCallbackUrlTransferPost)
+            .unwrap();
+        assert!(transfer_post.missing_placeholders.is_empty());
+        assert!(matches!(transfer_post.error, CallbackEndpointError::InvalidHostname(_)));
+    }
+
+    #[test]
+    fn audit_all_audits_each_participant() {
+        let complete = all_callbacks("http://dfspa.example.com");
+        let audits = audit_all(vec![
+            ("dfspa".parse().unwrap(), &complete[..]),
+            ("dfspb".parse().unwrap(), &[][..]),
+        ]);
+        assert_eq!(audits.len(), 2);
+        assert!(audits[0].is_ok());
+        assert!(!audits[1].is_ok());
+        assert_eq!(audits[1].name, "dfspb".parse().unwrap());
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use crate::request::{NoBody, impl_request};
+use crate::typescript::ts_type_alias;
+use crate::central_ledger::CentralLedgerRequest;
+
+// GET /enums returns the contents of central-ledger's enumeration tables, each as a map from
+// name to ID, keyed by the camelCase table name. Most tables have integer IDs, but some, such as
+// transferState, use the name as the ID.
+// https://github.com/mojaloop/central-ledger/blob/01435fda1d61093b2e20ff2385e8d65393dac640/src/lib/enum.js
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub enum EnumId {
+    Number(u64),
+    Name(String),
+}
+
+ts_type_alias!(EnumId = "number | string");
+
+/// An enumeration table, mapping name to ID
+pub type LedgerEnum = HashMap<String, EnumId>;
+
+/// The enumeration tables most useful for interpreting ledger state. Any others are in `other`,
+/// as they are returned: not every table central-ledger adds is guaranteed to be a map from name
+/// to ID, and one that isn't shouldn't fail the whole response. See [`LedgerEnums::table`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEnums {
+    #[serde(default)]
+    pub transfer_state: LedgerEnum,
+    #[serde(default)]
+    pub transfer_participant_role_type: LedgerEnum,
+    #[serde(default)]
+    pub ledger_account_type: LedgerEnum,
+    #[serde(default)]
+    pub ledger_entry_type: LedgerEnum,
+    #[serde(default)]
+    pub participant_limit_type: LedgerEnum,
+    #[serde(default)]
+    pub settlement_state: LedgerEnum,
+    #[serde(default)]
+    pub settlement_window_state: LedgerEnum,
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>,
+}
+
+impl LedgerEnums {
+    /// One of the tables in `other`, by its camelCase name. None where there's no such table or
+    /// it isn't a map from name to ID.
+    pub fn table(&self, name: &str) -> Option<LedgerEnum> {
+        self.other.get(name).and_then(|table| serde_json::from_value(table.clone()).ok())
+    }
+}
+
+ts_type_alias!(
+    LedgerEnums = "{ transferState: Record<string, EnumId>, transferParticipantRoleType: Record<string, EnumId>, \
+        ledgerAccountType: Record<string, EnumId>, ledgerEntryType: Record<string, EnumId>, \
+        participantLimitType: Record<string, EnumId>, settlementState: Record<string, EnumId>, \
+        settlementWindowState: Record<string, EnumId> } & Record<string, unknown>",
+    EnumId
+);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct GetEnums;
+
+impl_request! {
+    GetEnums: CentralLedgerRequest,
+    GET "/enums";
+    body: NoBody;
+    response: LedgerEnums;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ledger_enums_fixture() {
+        let enums: LedgerEnums = serde_json::from_value(serde_json::json!({
+            "transferState": {
+                "RECEIVED_PREPARE": "RECEIVED_PREPARE",
+                "COMMITTED": "COMMITTED",
+            },
+            "ledgerAccountType": {
+                "POSITION": 1,
+                "SETTLEMENT": 2,
+                "HUB_RECONCILIATION": 3,
+            },
+            "participantLimitType": { "NET_DEBIT_CAP": 1 },
+            "endpointType": { "FSPIOP_CALLBACK_URL_TRANSFER_POST": 4 },
+            "transferEventAction": ["PREPARE", "COMMIT"],
+        })).unwrap();
+        assert_eq!(enums.transfer_state["COMMITTED"], EnumId::Name("COMMITTED".to_string()));
+        assert_eq!(enums.ledger_account_type["SETTLEMENT"], EnumId::Number(2));
+        assert_eq!(enums.participant_limit_type.len(), 1);
+        assert!(enums.settlement_state.is_empty());
+        assert_eq!(enums.other.len(), 2);
+        assert_eq!(
+            enums.table("endpointType").unwrap()["FSPIOP_CALLBACK_URL_TRANSFER_POST"],
+            EnumId::Number(4),
+        );
+        assert!(enums.table("transferEventAction").is_none());
+        assert!(enums.table("missing").is_none());
+    }
+}
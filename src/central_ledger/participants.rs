@@ -1,8 +1,14 @@
 use serde::{Serialize, Deserialize};
+use std::convert::TryFrom;
 use fspiox_api::{Currency, FspId, CorrelationId, Money, DateTime, Amount};
 use derive_more::Display;
 use strum_macros::EnumIter;
+use strum::IntoEnumIterator;
+use std::collections::HashMap;
 use strum_macros::EnumString;
+use crate::request::{Request, NoBody, impl_request};
+use crate::typescript::ts_type_alias;
+use crate::central_ledger::CentralLedgerRequest;
 
 #[cfg(feature = "typescript_types")]
 use ts_rs::TS;
@@ -12,14 +18,6 @@ use ts_rs::TS;
 // - correct String vs &'static str etc. usage
 // - module structure:
 //   - in subdirectories like ./{name}/initialPositionAndLimits ?
-//   - a macro for creating impl CentralLedgerRequest so we can sorta do this:
-//       #[central_ledger_request(method = "POST", path = "/participants/{name}/initialPositionAndLimits")]
-//     But how do we manage those path parameters? The path parameters are typed.
-//     How do other request/server libraries handle this? Can we copy one of those? Or just _use one_? There's some argument that each of the requests
-//     should just have its path parameters as a typed struct field, then it should have a
-//     to_request method that turns it into a CentralLedgerRequest, with its path filled in with
-//     actual values (i.e. /participants/some_fsp/accounts, rather than
-//     /participants/{name}/accounts), or something..?)
 
 #[derive(Serialize, Deserialize, Debug, Display, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
@@ -28,33 +26,67 @@ pub struct AccountId(u64);
 #[serde(rename_all = "camelCase")]
 pub struct SettlementAccountId(u64);
 
-// TODO: contribute a PR to ts-rs to make this the default implementation for a newtype
-#[cfg(feature = "typescript_types")]
-impl TS for SettlementAccountId {
-    fn name() -> String {
-        "SettlementAccountId".to_string()
+ts_type_alias!(SettlementAccountId = "number");
+
+/// Central-ledger represents activity flags as 0 or 1, and in some responses as "0" or "1". This
+/// deserializes any of those, or true/false or "true"/"false", and serializes as a boolean.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsActive {
+    Yes,
+    No,
+}
+
+impl From<bool> for IsActive {
+    fn from(item: bool) -> Self {
+        if item { IsActive::Yes } else { IsActive::No }
+    }
+}
+
+impl From<IsActive> for bool {
+    fn from(item: IsActive) -> Self {
+        item == IsActive::Yes
     }
+}
 
-    fn dependencies() -> Vec<(std::any::TypeId, String)> {
-        Vec::new()
+impl Serialize for IsActive {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bool(bool::from(*self))
     }
+}
 
-    fn transparent() -> bool { false }
+impl<'de> Deserialize<'de> for IsActive {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, Unexpected};
 
-    fn decl() -> String {
-        "type SettlementAccountId = number".to_string()
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum BoolOrIntOrString {
+            Bool(bool),
+            Int(u64),
+            String(String),
+        }
+
+        match BoolOrIntOrString::deserialize(deserializer)? {
+            BoolOrIntOrString::Bool(b) => Ok(b.into()),
+            BoolOrIntOrString::Int(1) => Ok(IsActive::Yes),
+            BoolOrIntOrString::Int(0) => Ok(IsActive::No),
+            BoolOrIntOrString::Int(i) =>
+                Err(D::Error::invalid_value(Unexpected::Unsigned(i), &"0 or 1")),
+            BoolOrIntOrString::String(s) => match s.as_str() {
+                "1" | "true" => Ok(IsActive::Yes),
+                "0" | "false" => Ok(IsActive::No),
+                _ => Err(D::Error::invalid_value(Unexpected::Str(&s), &"\"0\", \"1\", \"true\" or \"false\"")),
+            },
+        }
     }
 }
 
-// TODO: need custom deserializer here: https://stackoverflow.com/a/65576570
-// Even better might be to serialize/deserialize as a boolean.
-#[cfg_attr(feature = "typescript_types", derive(TS))]
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
-pub enum IsActive {
-    #[serde(rename = "1")]
-    Yes,
-    #[serde(rename = "0")]
-    No,
+ts_type_alias!(IsActive = "boolean");
+
+/// For `bool` activity flags: `#[serde(deserialize_with = "deserialize_is_active")]`. See
+/// [`IsActive`].
+pub fn deserialize_is_active<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    IsActive::deserialize(deserializer).map(bool::from)
 }
 
 #[cfg_attr(feature = "typescript_types", derive(TS))]
@@ -117,15 +149,134 @@ pub enum LimitType {
     NetDebitCap,
 }
 
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositiveAmountError {
+    #[error("Amount must be greater than zero")]
+    NotPositive,
+    #[error("Invalid amount")]
+    InvalidAmount,
+}
+
+/// An [`Amount`] greater than zero, as required for limit values. Unlike `Amount`, this is
+/// serialized as a JSON number, which is what central-ledger expects for limits, and deserialized
+/// from either a number or a string.
+#[derive(Debug, Clone, Copy)]
+pub struct PositiveAmount(Amount);
+
+impl PositiveAmount {
+    pub fn new(amount: Amount) -> Result<Self, PositiveAmountError> {
+        let decimal = crate::amount::to_decimal(&amount)
+            .map_err(|_| PositiveAmountError::InvalidAmount)?;
+        if decimal > rust_decimal::Decimal::ZERO {
+            Ok(PositiveAmount(amount))
+        } else {
+            Err(PositiveAmountError::NotPositive)
+        }
+    }
+
+    pub fn amount(&self) -> Amount {
+        self.0
+    }
+}
+
+impl From<PositiveAmount> for Amount {
+    fn from(item: PositiveAmount) -> Self {
+        item.0
+    }
+}
+
+impl TryFrom<Amount> for PositiveAmount {
+    type Error = PositiveAmountError;
+
+    fn try_from(amount: Amount) -> Result<Self, Self::Error> {
+        PositiveAmount::new(amount)
+    }
+}
+
+impl core::str::FromStr for PositiveAmount {
+    type Err = PositiveAmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let amount: Amount = serde_json::from_value(serde_json::Value::String(s.to_string()))
+            .map_err(|_| PositiveAmountError::InvalidAmount)?;
+        PositiveAmount::new(amount)
+    }
+}
+
+// Written and read as a raw JSON number via the exact decimal, as going through a u64 or f64 would
+// lose the precision of large amounts and of most fractions.
+impl Serialize for PositiveAmount {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+        let decimal = crate::amount::to_decimal(&self.0).map_err(S::Error::custom)?;
+        crate::amount::number::serialize(&decimal, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PositiveAmount {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        crate::amount::number::deserialize(deserializer)?
+            .to_string()
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+ts_type_alias!(PositiveAmount = "number");
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq)]
+pub enum AlarmPercentageError {
+    #[error("Alarm percentage must be a number")]
+    NotANumber,
+    #[error("Alarm percentage must be between 0 and 100, found {0}")]
+    OutOfRange(f64),
+}
+
+/// The percentage of a limit at which central-ledger raises an alarm, in the range [0, 100].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, PartialOrd, Display)]
+#[serde(try_from = "f64")]
+pub struct AlarmPercentage(f64);
+
+impl AlarmPercentage {
+    pub fn new(value: f64) -> Result<Self, AlarmPercentageError> {
+        if (0.0..=100.0).contains(&value) {
+            Ok(AlarmPercentage(value))
+        } else {
+            Err(AlarmPercentageError::OutOfRange(value))
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+impl TryFrom<f64> for AlarmPercentage {
+    type Error = AlarmPercentageError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        AlarmPercentage::new(value)
+    }
+}
+
+impl core::str::FromStr for AlarmPercentage {
+    type Err = AlarmPercentageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AlarmPercentage::new(s.parse().map_err(|_| AlarmPercentageError::NotANumber)?)
+    }
+}
+
+ts_type_alias!(AlarmPercentage = "number");
+
 #[cfg_attr(feature = "typescript_types", derive(TS))]
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub struct Limit {
     #[cfg_attr(feature = "typescript_types", ts(rename = "type"))]
     pub r#type: LimitType,
-    // TODO: this should be Money, but "positive money"
-    // Or should it be "positive integer money"?
-    pub value: u32,
+    pub value: PositiveAmount,
 }
 
 #[cfg_attr(feature = "typescript_types", derive(TS))]
@@ -134,10 +285,8 @@ pub struct Limit {
 pub struct ParticipantLimit {
     #[cfg_attr(feature = "typescript_types", ts(rename = "type"))]
     pub r#type: LimitType,
-    // TODO: this should be Money, but "positive money"
-    // Or should it be "positive integer money"?
-    pub value: u32,
-    pub alarm_percentage: u8, // TODO: "number" in the spec. Probably needs to be [0,100].
+    pub value: PositiveAmount,
+    pub alarm_percentage: AlarmPercentage,
 }
 
 #[cfg_attr(feature = "typescript_types", derive(TS))]
@@ -158,23 +307,296 @@ pub enum ParticipantFundsInOutAction {
     RecordFundsOutPrepareReserve,
 }
 
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringLengthError {
+    #[error("Value must not be empty")]
+    Empty,
+    #[error("Value must be at most {max} characters")]
+    TooLong { max: usize },
+}
+
+// A string newtype whose value is checked, and possibly normalized, by `$validate`, a
+// fn(String) -> Result<String, $error>, on construction and deserialization.
+macro_rules! validated_string {
+    ($(#[$attr:meta])* $name:ident, $error:ty, $validate:path) => {
+        $(#[$attr])*
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Display)]
+        #[serde(try_from = "String")]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn new(value: impl Into<String>) -> Result<Self, $error> {
+                $validate(value.into()).map($name)
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = $error;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                $name::new(value)
+            }
+        }
+
+        impl core::str::FromStr for $name {
+            type Err = $error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::new(s)
+            }
+        }
+
+        ts_type_alias!($name = "string");
+    };
+}
+
+// A non-empty string newtype with a maximum length in characters
+macro_rules! bounded_string {
+    ($(#[$attr:meta])* $name:ident, $max:expr) => {
+        validated_string!($(#[$attr])* $name, StringLengthError, $name::validate);
+
+        impl $name {
+            pub const MAX_LEN: usize = $max;
+
+            fn validate(value: String) -> Result<String, StringLengthError> {
+                match value.chars().count() {
+                    0 => Err(StringLengthError::Empty),
+                    n if n > Self::MAX_LEN => Err(StringLengthError::TooLong { max: Self::MAX_LEN }),
+                    _ => Ok(value),
+                }
+            }
+        }
+    };
+}
+
+// The swagger only specifies type: string for externalReference and reason, but central-ledger
+// requires them to be present and non-empty, and stores them in columns of limited size:
+// transferExtension.value and transferStateChange.reason respectively.
+bounded_string!(
+    /// The external reference of a funds in/out transfer
+    ExternalReference, 128
+);
+bounded_string!(
+    /// The reason for a funds in/out transfer
+    FundsInOutReason, 512
+);
+
 #[cfg_attr(feature = "typescript_types", derive(TS))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ParticipantFundsInOut {
     pub transfer_id: CorrelationId,
     pub action: ParticipantFundsInOutAction,
-    pub external_reference: String, // From the spec, external_reference is only type: string, with no further validation
-    pub reason: String, // From the spec, reason is only type: string, with no further validation
+    pub external_reference: ExternalReference,
+    pub reason: FundsInOutReason,
     pub amount: Money,
 }
 
+/// The FSPIOP party ID types. Variants are named as they are on the wire.
+#[allow(non_camel_case_types)]
 #[cfg_attr(feature = "typescript_types", derive(TS))]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumString, Display)]
 pub enum PartyIdType {
     MSISDN,
+    EMAIL,
+    PERSONAL_ID,
+    BUSINESS,
+    DEVICE,
+    ACCOUNT_ID,
+    IBAN,
+    ALIAS,
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MsisdnError {
+    #[error("MSISDN must contain only digits, without a leading +")]
+    NotNumeric,
+    #[error("MSISDN must not start with 0")]
+    LeadingZero,
+    #[error("MSISDN must be between {min} and {max} digits")]
+    InvalidLength { min: usize, max: usize },
+}
+
+validated_string!(
+    /// An MSISDN in international format without the leading `+`, i.e. country code followed
+    /// by subscriber number. This is the format accepted by the account-lookup service's MSISDN
+    /// oracle: five to fifteen digits, not starting with zero.
+    Msisdn, MsisdnError, Msisdn::validate
+);
+
+impl Msisdn {
+    pub const MIN_LEN: usize = 5;
+    // E.164
+    pub const MAX_LEN: usize = 15;
+
+    fn validate(value: String) -> Result<String, MsisdnError> {
+        if !value.chars().all(|c| c.is_ascii_digit()) {
+            return Err(MsisdnError::NotNumeric);
+        }
+        if !(Self::MIN_LEN..=Self::MAX_LEN).contains(&value.len()) {
+            return Err(MsisdnError::InvalidLength { min: Self::MIN_LEN, max: Self::MAX_LEN });
+        }
+        if value.starts_with('0') {
+            return Err(MsisdnError::LeadingZero);
+        }
+        Ok(value)
+    }
 }
 
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IbanError {
+    #[error("IBAN must contain only upper case letters and digits, without spaces")]
+    InvalidCharacters,
+    #[error("IBAN must be between {min} and {max} characters")]
+    InvalidLength { min: usize, max: usize },
+    #[error("IBAN must start with a two letter country code followed by two check digits")]
+    InvalidPrefix,
+    #[error("IBAN check digits are incorrect")]
+    InvalidChecksum,
+}
+
+validated_string!(
+    /// An IBAN in electronic format: upper case, no spaces. The structure and ISO 7064 mod 97-10
+    /// check digits are validated; the country-specific length and BBAN format are not.
+    Iban, IbanError, Iban::validate
+);
+
+impl Iban {
+    pub const MIN_LEN: usize = 15;
+    pub const MAX_LEN: usize = 34;
+
+    fn validate(value: String) -> Result<String, IbanError> {
+        if !value.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+            return Err(IbanError::InvalidCharacters);
+        }
+        if !(Self::MIN_LEN..=Self::MAX_LEN).contains(&value.len()) {
+            return Err(IbanError::InvalidLength { min: Self::MIN_LEN, max: Self::MAX_LEN });
+        }
+        let bytes = value.as_bytes();
+        if !bytes[..2].iter().all(u8::is_ascii_uppercase) || !bytes[2..4].iter().all(u8::is_ascii_digit) {
+            return Err(IbanError::InvalidPrefix);
+        }
+        // Move the country code and check digits to the end, replace letters with 10..=35, and
+        // take the remainder mod 97 digit by digit to avoid overflow.
+        let remainder = value[4..].chars().chain(value[..4].chars())
+            .map(|c| c.to_digit(36).unwrap())
+            .fold(0, |acc, d| if d < 10 { (acc * 10 + d) % 97 } else { (acc * 100 + d) % 97 });
+        if remainder != 1 {
+            return Err(IbanError::InvalidChecksum);
+        }
+        Ok(value)
+    }
+}
+
+// The FSPIOP API specifies only that a party identifier is at most 128 characters for the
+// remaining party ID types; their formats are scheme-specific.
+bounded_string!(
+    /// A party identifier of type PERSONAL_ID, e.g. a passport or national ID number. The kind
+    /// of personal ID is given by the party sub ID.
+    PersonalId, 128
+);
+bounded_string!(
+    /// A party identifier of type BUSINESS
+    BusinessId, 128
+);
+bounded_string!(
+    /// A party identifier of type DEVICE
+    DeviceId, 128
+);
+bounded_string!(
+    /// A party identifier of type ACCOUNT_ID
+    AccountIdentifier, 128
+);
+bounded_string!(
+    /// A party identifier of type ALIAS
+    Alias, 128
+);
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum PartyIdError {
+    #[error("Invalid MSISDN: {0}")]
+    Msisdn(#[from] MsisdnError),
+    #[error("Invalid email address: {0}")]
+    Email(#[from] EmailAddressError),
+    #[error("Invalid IBAN: {0}")]
+    Iban(#[from] IbanError),
+    #[error("Invalid {0} party identifier: {1}")]
+    Identifier(PartyIdType, StringLengthError),
+}
+
+/// A party identifier together with its type, serialized in the FSPIOP `PartyIdInfo` form, e.g.
+/// `{ "partyIdType": "MSISDN", "partyIdentifier": "447700900123" }`. Displays as the identifier
+/// alone.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Display)]
+#[serde(tag = "partyIdType", content = "partyIdentifier")]
+pub enum PartyId {
+    #[serde(rename = "MSISDN")]
+    Msisdn(Msisdn),
+    #[serde(rename = "EMAIL")]
+    Email(EmailAddress),
+    #[serde(rename = "PERSONAL_ID")]
+    PersonalId(PersonalId),
+    #[serde(rename = "BUSINESS")]
+    Business(BusinessId),
+    #[serde(rename = "DEVICE")]
+    Device(DeviceId),
+    #[serde(rename = "ACCOUNT_ID")]
+    AccountId(AccountIdentifier),
+    #[serde(rename = "IBAN")]
+    Iban(Iban),
+    #[serde(rename = "ALIAS")]
+    Alias(Alias),
+}
+
+impl PartyId {
+    /// Validates `identifier` as a party identifier of type `party_id_type`
+    pub fn new(party_id_type: PartyIdType, identifier: &str) -> Result<PartyId, PartyIdError> {
+        let length_err = |e| PartyIdError::Identifier(party_id_type, e);
+        Ok(match party_id_type {
+            PartyIdType::MSISDN => PartyId::Msisdn(Msisdn::new(identifier)?),
+            PartyIdType::EMAIL => PartyId::Email(EmailAddress::new(identifier)?),
+            PartyIdType::PERSONAL_ID => PartyId::PersonalId(PersonalId::new(identifier).map_err(length_err)?),
+            PartyIdType::BUSINESS => PartyId::Business(BusinessId::new(identifier).map_err(length_err)?),
+            PartyIdType::DEVICE => PartyId::Device(DeviceId::new(identifier).map_err(length_err)?),
+            PartyIdType::ACCOUNT_ID => PartyId::AccountId(AccountIdentifier::new(identifier).map_err(length_err)?),
+            PartyIdType::IBAN => PartyId::Iban(Iban::new(identifier)?),
+            PartyIdType::ALIAS => PartyId::Alias(Alias::new(identifier).map_err(length_err)?),
+        })
+    }
+
+    pub fn party_id_type(&self) -> PartyIdType {
+        match self {
+            PartyId::Msisdn(_) => PartyIdType::MSISDN,
+            PartyId::Email(_) => PartyIdType::EMAIL,
+            PartyId::PersonalId(_) => PartyIdType::PERSONAL_ID,
+            PartyId::Business(_) => PartyIdType::BUSINESS,
+            PartyId::Device(_) => PartyIdType::DEVICE,
+            PartyId::AccountId(_) => PartyIdType::ACCOUNT_ID,
+            PartyId::Iban(_) => PartyIdType::IBAN,
+            PartyId::Alias(_) => PartyIdType::ALIAS,
+        }
+    }
+
+    pub fn identifier(&self) -> &str {
+        match self {
+            PartyId::Msisdn(id) => id.as_str(),
+            PartyId::Email(id) => id.as_str(),
+            PartyId::PersonalId(id) => id.as_str(),
+            PartyId::Business(id) => id.as_str(),
+            PartyId::Device(id) => id.as_str(),
+            PartyId::AccountId(id) => id.as_str(),
+            PartyId::Iban(id) => id.as_str(),
+            PartyId::Alias(id) => id.as_str(),
+        }
+    }
+}
+
+ts_type_alias!(PartyId = "{ partyIdType: PartyIdType, partyIdentifier: string }", PartyIdType);
+
 #[cfg_attr(feature = "typescript_types", derive(TS))]
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
@@ -182,7 +604,8 @@ pub struct ParticipantAccount {
     pub id: SettlementAccountId,
     pub ledger_account_type: AnyAccountType,
     pub currency: Currency,
-    pub is_active: u8,
+    #[serde(deserialize_with = "deserialize_is_active")]
+    pub is_active: bool,
 }
 
 #[cfg_attr(feature = "typescript_types", derive(TS))]
@@ -191,11 +614,12 @@ pub struct ParticipantAccount {
 pub struct Participant {
     pub name: FspId,
     pub id: String,
-    // This should be DateTime, but the response comes back as a nested string. I.e.
+    // The response usually comes back as a nested string. I.e.
     // { created: "\"2021-01-01T01:23:34Z\"" }
-    #[serde(with = "serde_with::json::nested")]
+    #[serde(with = "crate::datetime::lenient")]
     pub created: DateTime,
-    pub is_active: u8,
+    #[serde(deserialize_with = "deserialize_is_active")]
+    pub is_active: bool,
     pub accounts: Vec<ParticipantAccount>,
 }
 
@@ -234,10 +658,11 @@ pub struct DfspAccount {
     pub id: SettlementAccountId,
     pub ledger_account_type: AnyAccountType,
     pub currency: Currency,
-    // TODO: this is an enum with value 0 or 1. Probably use IsActive (see earlier in this file).
-    pub is_active: u8,
+    #[serde(deserialize_with = "deserialize_is_active")]
+    pub is_active: bool,
     pub value: Amount,
     pub reserved_value: Amount,
+    #[serde(with = "crate::datetime::lenient")]
     pub changed_date: DateTime,
 }
 
@@ -245,6 +670,8 @@ pub type DfspAccounts = Vec<DfspAccount>;
 
 pub type CallbackUrls = Vec<CallbackUrl>;
 
+pub type Endpoints = Vec<Endpoint>;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GetCallbackUrls {
@@ -261,6 +688,7 @@ pub struct GetDfspAccounts {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct CurrencyIsActive {
+    #[serde(deserialize_with = "deserialize_is_active")]
     pub is_active: bool,
 }
 
@@ -297,8 +725,9 @@ pub struct PostParticipantSettlementFunds {
     pub funds: ParticipantFundsInOut,
 }
 
+// https://github.com/mojaloop/central-ledger/blob/master/seeds/endpointType.js
 #[cfg_attr(feature = "typescript_types", derive(TS))]
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, EnumIter, Display)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, Display)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum FspiopCallbackType {
     // The prefixes on these enums is fairly redundant, but mirrors the enums used in the API
@@ -306,32 +735,221 @@ pub enum FspiopCallbackType {
     FspiopCallbackUrlParticipantBatchPutError,
     FspiopCallbackUrlParticipantPut,
     FspiopCallbackUrlParticipantPutError,
+    FspiopCallbackUrlParticipantSubIdPut,
+    FspiopCallbackUrlParticipantSubIdPutError,
+    FspiopCallbackUrlParticipantSubIdDelete,
     FspiopCallbackUrlPartiesGet,
     FspiopCallbackUrlPartiesPut,
     FspiopCallbackUrlPartiesPutError,
+    FspiopCallbackUrlPartiesSubIdGet,
+    FspiopCallbackUrlPartiesSubIdPut,
+    FspiopCallbackUrlPartiesSubIdPutError,
     FspiopCallbackUrlQuotes,
+    FspiopCallbackUrlBulkQuotes,
     FspiopCallbackUrlTransferError,
     FspiopCallbackUrlTransferPost,
     FspiopCallbackUrlTransferPut,
+    FspiopCallbackUrlBulkTransferPost,
+    FspiopCallbackUrlBulkTransferPut,
+    FspiopCallbackUrlBulkTransferError,
+    FspiopCallbackUrlAuthorizations,
+    FspiopCallbackUrlTrxReqService,
 }
 
-const fn get_callback_path(callback_type: FspiopCallbackType) -> &'static str {
-    use FspiopCallbackType::*;
-    match callback_type {
-        FspiopCallbackUrlParticipantBatchPut      => "/participants/{{requestId}}",
-        FspiopCallbackUrlParticipantBatchPutError => "/participants/{{requestId}}/error",
-        FspiopCallbackUrlParticipantPut           => "/participants/{{partyIdType}}/{{partyIdentifier}}",
-        FspiopCallbackUrlParticipantPutError      => "/participants/{{partyIdType}}/{{partyIdentifier}}/error",
-        FspiopCallbackUrlPartiesGet               => "/parties/{{partyIdType}}/{{partyIdentifier}}",
-        FspiopCallbackUrlPartiesPut               => "/parties/{{partyIdType}}/{{partyIdentifier}}",
-        FspiopCallbackUrlPartiesPutError          => "/parties/{{partyIdType}}/{{partyIdentifier}}/error",
-        FspiopCallbackUrlQuotes                   => "", // TODO: is this correct?
-        FspiopCallbackUrlTransferError            => "/transfers/{{transferId}}/error",
-        FspiopCallbackUrlTransferPost             => "/transfers",
-        FspiopCallbackUrlTransferPut              => "/transfers/{{transferId}}",
+impl FspiopCallbackType {
+    /// The path template central-ledger expects to follow the DFSP hostname for this endpoint
+    /// type. Placeholders such as `{{transferId}}` are filled in by the hub when it makes a
+    /// callback.
+    pub const fn path_template(self) -> &'static str {
+        use FspiopCallbackType::*;
+        match self {
+            FspiopCallbackUrlParticipantBatchPut      => "/participants/{{requestId}}",
+            FspiopCallbackUrlParticipantBatchPutError => "/participants/{{requestId}}/error",
+            FspiopCallbackUrlParticipantPut           => "/participants/{{partyIdType}}/{{partyIdentifier}}",
+            FspiopCallbackUrlParticipantPutError      => "/participants/{{partyIdType}}/{{partyIdentifier}}/error",
+            FspiopCallbackUrlParticipantSubIdPut      => "/participants/{{partyIdType}}/{{partyIdentifier}}/{{partySubIdOrType}}",
+            FspiopCallbackUrlParticipantSubIdPutError => "/participants/{{partyIdType}}/{{partyIdentifier}}/{{partySubIdOrType}}/error",
+            FspiopCallbackUrlParticipantSubIdDelete   => "/participants/{{partyIdType}}/{{partyIdentifier}}/{{partySubIdOrType}}",
+            FspiopCallbackUrlPartiesGet               => "/parties/{{partyIdType}}/{{partyIdentifier}}",
+            FspiopCallbackUrlPartiesPut               => "/parties/{{partyIdType}}/{{partyIdentifier}}",
+            FspiopCallbackUrlPartiesPutError          => "/parties/{{partyIdType}}/{{partyIdentifier}}/error",
+            FspiopCallbackUrlPartiesSubIdGet          => "/parties/{{partyIdType}}/{{partyIdentifier}}/{{partySubIdOrType}}",
+            FspiopCallbackUrlPartiesSubIdPut          => "/parties/{{partyIdType}}/{{partyIdentifier}}/{{partySubIdOrType}}",
+            FspiopCallbackUrlPartiesSubIdPutError     => "/parties/{{partyIdType}}/{{partyIdentifier}}/{{partySubIdOrType}}/error",
+            // The quoting service, and the other services with a bare hostname here, append the
+            // resource path themselves.
+            FspiopCallbackUrlQuotes                   => "",
+            FspiopCallbackUrlBulkQuotes               => "",
+            FspiopCallbackUrlTransferError            => "/transfers/{{transferId}}/error",
+            FspiopCallbackUrlTransferPost             => "/transfers",
+            FspiopCallbackUrlTransferPut              => "/transfers/{{transferId}}",
+            FspiopCallbackUrlBulkTransferPost         => "/bulkTransfers",
+            FspiopCallbackUrlBulkTransferPut          => "/bulkTransfers/{{id}}",
+            FspiopCallbackUrlBulkTransferError        => "/bulkTransfers/{{id}}/error",
+            FspiopCallbackUrlAuthorizations           => "",
+            FspiopCallbackUrlTrxReqService            => "",
+        }
+    }
+
+    /// The placeholders in this endpoint type's path template, e.g. `{{transferId}}`
+    pub fn placeholders(self) -> Vec<&'static str> {
+        let mut result = Vec::new();
+        let mut rest = self.path_template();
+        while let Some(start) = rest.find("{{") {
+            match rest[start..].find("}}") {
+                Some(len) => {
+                    result.push(&rest[start..start + len + 2]);
+                    rest = &rest[start + len + 2..];
+                }
+                None => break,
+            }
+        }
+        result
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum CallbackEndpointError {
+    #[error("Endpoint value {value} does not end with the {callback_type} path template {template}")]
+    TemplateMismatch {
+        callback_type: FspiopCallbackType,
+        template: &'static str,
+        value: String,
+    },
+    #[error("Invalid callback hostname: {0}")]
+    InvalidHostname(#[from] CallbackHostnameError),
+}
+
+/// A URL callback endpoint in terms of its parts: the DFSP hostname and the callback type, which
+/// determines the path template. [`CallbackEndpoint::render`] produces the value central-ledger
+/// stores; `CallbackEndpoint::try_from(&CallbackUrl)` recovers the parts from a stored value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackEndpoint {
+    pub callback_type: FspiopCallbackType,
+    pub hostname: CallbackHostname,
+}
+
+impl CallbackEndpoint {
+    pub fn render(&self) -> String {
+        format!("{}{}", self.hostname, self.callback_type.path_template())
+    }
+}
+
+impl TryFrom<&CallbackUrl> for CallbackEndpoint {
+    type Error = CallbackEndpointError;
+
+    fn try_from(url: &CallbackUrl) -> Result<Self, Self::Error> {
+        let template = url.r#type.path_template();
+        let hostname = url.value.strip_suffix(template)
+            .ok_or_else(|| CallbackEndpointError::TemplateMismatch {
+                callback_type: url.r#type,
+                template,
+                value: url.value.clone(),
+            })?;
+        Ok(CallbackEndpoint {
+            callback_type: url.r#type,
+            hostname: CallbackHostname::new(hostname)?,
+        })
+    }
+}
+
+impl From<&CallbackEndpoint> for CallbackUrl {
+    fn from(endpoint: &CallbackEndpoint) -> Self {
+        CallbackUrl {
+            r#type: endpoint.callback_type,
+            value: endpoint.render(),
+        }
     }
 }
 
+// Central-ledger's email endpoint types share the /participants/{name}/endpoints route with the
+// callback URLs, but their value is an email address to which the hub sends notifications.
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, Display)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EmailEndpointType {
+    NetDebitCapThresholdBreachEmail,
+    NetDebitCapAdjustmentEmail,
+    SettlementTransferPositionChangeEmail,
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailAddressError {
+    #[error("Email address must have the form local@domain")]
+    InvalidFormat,
+    #[error("Email address must not contain whitespace")]
+    ContainsWhitespace,
+    #[error("Email address must be at most {max} characters")]
+    TooLong { max: usize },
+}
+
+validated_string!(
+    /// An email address. Validation is deliberately loose: a single `@` separating a non-empty
+    /// local part from a domain containing a `.`, no whitespace, and at most 254 characters.
+    EmailAddress, EmailAddressError, EmailAddress::validate
+);
+
+impl EmailAddress {
+    pub const MAX_LEN: usize = 254;
+
+    fn validate(value: String) -> Result<String, EmailAddressError> {
+        if value.chars().count() > Self::MAX_LEN {
+            return Err(EmailAddressError::TooLong { max: Self::MAX_LEN });
+        }
+        if value.chars().any(char::is_whitespace) {
+            return Err(EmailAddressError::ContainsWhitespace);
+        }
+        let mut parts = value.split('@');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(local), Some(domain), None)
+                if !local.is_empty() &&
+                    domain.contains('.') &&
+                    !domain.starts_with('.') &&
+                    !domain.ends_with('.') => Ok(value),
+            _ => Err(EmailAddressError::InvalidFormat),
+        }
+    }
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EmailEndpoint {
+    #[cfg_attr(feature = "typescript_types", ts(rename = "type"))]
+    pub r#type: EmailEndpointType,
+    pub value: EmailAddress,
+}
+
+/// An endpoint as returned by GET /participants/{name}/endpoints. Endpoint types this crate
+/// doesn't model, such as ALARM_NOTIFICATION_URL, are returned as `Other`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Endpoint {
+    Callback(CallbackUrl),
+    Email(EmailEndpoint),
+    Other {
+        r#type: String,
+        value: String,
+    },
+}
+
+ts_type_alias!(Endpoint = "CallbackUrl | EmailEndpoint | { type: string, value: string }", CallbackUrl, EmailEndpoint);
+
+/// The callback URLs among `endpoints`
+pub fn callback_urls(endpoints: &[Endpoint]) -> impl Iterator<Item = &CallbackUrl> {
+    endpoints.iter().filter_map(|endpoint| match endpoint {
+        Endpoint::Callback(url) => Some(url),
+        _ => None,
+    })
+}
+
+/// The email endpoints among `endpoints`
+pub fn email_endpoints(endpoints: &[Endpoint]) -> impl Iterator<Item = &EmailEndpoint> {
+    endpoints.iter().filter_map(|endpoint| match endpoint {
+        Endpoint::Email(email) => Some(email),
+        _ => None,
+    })
+}
+
 #[cfg_attr(feature = "typescript_types", derive(TS))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CallbackUrl {
@@ -340,97 +958,586 @@ pub struct CallbackUrl {
     pub value: String,
 }
 
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum CallbackHostnameError {
+    #[error("Invalid callback URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+    #[error("Callback URL scheme must be http or https, found {0}")]
+    UnsupportedScheme(String),
+    #[error("Callback URL must not contain a query or fragment")]
+    QueryOrFragment,
+}
+
+validated_string!(
+    /// The base URL of a DFSP's FSPIOP API, to which callback paths are appended, e.g.
+    /// `http://dfsp.example.com:3000`. Must be an absolute http or https URL without a query or
+    /// fragment. Any trailing slash is removed, so that appending a callback path doesn't produce
+    /// an empty path segment.
+    CallbackHostname, CallbackHostnameError, CallbackHostname::validate
+);
+
+impl CallbackHostname {
+    fn validate(value: String) -> Result<String, CallbackHostnameError> {
+        let url = url::Url::parse(&value)?;
+        match url.scheme() {
+            "http" | "https" => {},
+            scheme => return Err(CallbackHostnameError::UnsupportedScheme(scheme.to_string())),
+        }
+        if url.query().is_some() || url.fragment().is_some() {
+            return Err(CallbackHostnameError::QueryOrFragment);
+        }
+        Ok(value.trim_end_matches('/').to_string())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PostCallbackUrl {
     pub name: FspId,
     pub callback_type: FspiopCallbackType,
-    pub hostname: String,
+    pub hostname: CallbackHostname,
 }
 
-// TODO: usage of unwrap() on the result of the request builder tells us that we should replace
-// many of our types that can _only_ accept ASCII [[32-126], [128-255]].
-// https://docs.rs/http/0.2.4/http/header/struct.HeaderValue.html#method.from_bytes
-// In fact, here we're really assuming that none of these values contain non-printable ASCII
-// characters.
+#[derive(Debug, Clone)]
+pub struct PostEmailEndpoint {
+    pub name: FspId,
+    pub email_type: EmailEndpointType,
+    pub address: EmailAddress,
+}
 
-#[cfg(feature = "hyper")]
-pub mod requests {
-    use crate::central_ledger::participants::*;
-    use fspiox_api::clients::NoBody;
-    use crate::clients::requests::{get, post, put};
+/// One [`PostCallbackUrl`] for every URL callback type, all using `hostname` except where
+/// `overrides` specifies a different hostname for a type. This is what onboarding a DFSP requires.
+pub fn callback_registrations(
+    name: FspId,
+    hostname: &CallbackHostname,
+    overrides: &HashMap<FspiopCallbackType, CallbackHostname>,
+) -> Vec<PostCallbackUrl> {
+    FspiopCallbackType::iter()
+        .map(|callback_type| PostCallbackUrl {
+            name,
+            callback_type,
+            hostname: overrides.get(&callback_type).unwrap_or(hostname).clone(),
+        })
+        .collect()
+}
 
-    impl From<GetParticipantLimits> for http::Request<hyper::Body> {
-        fn from(req: GetParticipantLimits) -> http::Request<hyper::Body> {
-            get(format!("/participants/{}/limits", req.name).as_str(), &NoBody)
-        }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallbackMismatch {
+    Missing {
+        callback_type: FspiopCallbackType,
+        expected: String,
+    },
+    Different {
+        callback_type: FspiopCallbackType,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// The outcome of registering a DFSP's callbacks with one of the clients, whose errors are `E`
+#[derive(Debug)]
+pub struct CallbackRegistrationReport<E> {
+    /// The outcome of registering each callback type
+    pub results: Vec<(FspiopCallbackType, Result<(), E>)>,
+    /// Differences between the registered and stored callbacks, if verification was requested
+    pub mismatches: Option<Vec<CallbackMismatch>>,
+}
+
+impl<E> CallbackRegistrationReport<E> {
+    pub fn is_ok(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok()) &&
+            self.mismatches.iter().all(|mismatches| mismatches.is_empty())
     }
+}
 
-    impl From<PutParticipantLimit> for http::Request<hyper::Body> {
-        fn from(req: PutParticipantLimit) -> http::Request<hyper::Body> {
-            put(format!("/participants/{}/limits", req.name).as_str(), &req.limit)
+/// Compares the callback URLs `registrations` would create with the endpoints returned by
+/// [`GetCallbackUrls`], returning any that are missing or differ.
+pub fn verify_callbacks(registrations: &[PostCallbackUrl], actual: &[Endpoint]) -> Vec<CallbackMismatch> {
+    registrations
+        .iter()
+        .filter_map(|registration| {
+            let expected = registration.body().value;
+            match callback_urls(actual).find(|url| url.r#type == registration.callback_type) {
+                None => Some(CallbackMismatch::Missing {
+                    callback_type: registration.callback_type,
+                    expected,
+                }),
+                Some(url) if url.value != expected => Some(CallbackMismatch::Different {
+                    callback_type: registration.callback_type,
+                    expected,
+                    actual: url.value.clone(),
+                }),
+                Some(_) => None,
+            }
+        })
+        .collect()
+}
+
+// Path parameters such as FspId are percent-encoded by impl_request!, and anything that still
+// can't form a valid URI is reported as a crate::request::Error when the request is built.
+
+impl_request! {
+    GetParticipantLimits: CentralLedgerRequest,
+    GET "/participants/{}/limits", name;
+    body: NoBody;
+    response: Vec<NewParticipantLimit>;
+}
+
+impl_request! {
+    PutParticipantLimit: CentralLedgerRequest,
+    PUT "/participants/{}/limits", name;
+    body: NewParticipantLimit = |req| req.limit;
+    response: NoBody;
+}
+
+impl_request! {
+    PutParticipantAccount: CentralLedgerRequest,
+    PUT "/participants/{}/accounts/{}", name, account_id;
+    body: CurrencyIsActive = |req| CurrencyIsActive { is_active: req.set_active };
+    response: NoBody;
+}
+
+impl_request! {
+    PostHubAccount: CentralLedgerRequest,
+    POST "/participants/{}/accounts", name;
+    body: HubAccount = |req| req.account;
+    response: NoBody;
+}
+
+impl_request! {
+    PostCallbackUrl: CentralLedgerRequest,
+    POST "/participants/{}/endpoints", name;
+    body: CallbackUrl = |req| CallbackUrl::from(&CallbackEndpoint {
+        callback_type: req.callback_type,
+        hostname: req.hostname.clone(),
+    });
+    response: NoBody;
+}
+
+impl_request! {
+    PostEmailEndpoint: CentralLedgerRequest,
+    POST "/participants/{}/endpoints", name;
+    body: EmailEndpoint = |req| EmailEndpoint {
+        r#type: req.email_type,
+        value: req.address.clone(),
+    };
+    response: NoBody;
+}
+
+impl_request! {
+    PostParticipantSettlementFunds: CentralLedgerRequest,
+    POST "/participants/{}/accounts/{}", name, account_id;
+    body: ParticipantFundsInOut = |req| req.funds.clone();
+    response: NoBody;
+}
+
+impl_request! {
+    GetParticipants: CentralLedgerRequest,
+    GET "/participants";
+    body: NoBody;
+    response: Participants;
+}
+
+impl_request! {
+    GetCallbackUrls: CentralLedgerRequest,
+    GET "/participants/{}/endpoints", name;
+    body: NoBody;
+    response: Endpoints;
+}
+
+impl_request! {
+    GetDfspAccounts: CentralLedgerRequest,
+    GET "/participants/{}/accounts", name;
+    body: NoBody;
+    response: DfspAccounts;
+}
+
+impl_request! {
+    PostInitialPositionAndLimits: CentralLedgerRequest,
+    POST "/participants/{}/initialPositionAndLimits", name;
+    body: InitialPositionAndLimits = |req| req.initial_position_and_limits;
+    response: NoBody;
+}
+
+impl_request! {
+    PostParticipant: CentralLedgerRequest,
+    POST "/participants";
+    body: NewParticipant = |req| req.participant.clone();
+    response: Participant;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_active_accepts_each_representation() {
+        for (json, expected) in [
+            (serde_json::json!(1), IsActive::Yes),
+            (serde_json::json!(0), IsActive::No),
+            (serde_json::json!("1"), IsActive::Yes),
+            (serde_json::json!("0"), IsActive::No),
+            (serde_json::json!(true), IsActive::Yes),
+            (serde_json::json!(false), IsActive::No),
+            (serde_json::json!("true"), IsActive::Yes),
+            (serde_json::json!("false"), IsActive::No),
+        ] {
+            assert_eq!(serde_json::from_value::<IsActive>(json.clone()).unwrap(), expected, "{}", json);
         }
+        assert_eq!(serde_json::to_value(IsActive::Yes).unwrap(), serde_json::json!(true));
+        let currency: CurrencyIsActive = serde_json::from_str(r#"{"isActive":"0"}"#).unwrap();
+        assert!(!currency.is_active);
     }
 
-    impl From<PutParticipantAccount> for http::Request<hyper::Body> {
-        fn from(req: PutParticipantAccount) -> http::Request<hyper::Body> {
-            put(
-                format!("/participants/{}/accounts/{}", req.name, req.account_id).as_str(),
-                &CurrencyIsActive { is_active: req.set_active },
-            )
-        }
+    #[test]
+    fn is_active_rejects_other_values() {
+        let err = serde_json::from_value::<IsActive>(serde_json::json!(2)).unwrap_err();
+        assert!(err.to_string().contains("expected 0 or 1"), "{}", err);
+        let err = serde_json::from_value::<IsActive>(serde_json::json!("yes")).unwrap_err();
+        assert!(err.to_string().contains(r#"expected "0", "1", "true" or "false""#), "{}", err);
+        assert!(serde_json::from_value::<IsActive>(serde_json::json!(null)).is_err());
     }
 
-    impl From<PostHubAccount> for http::Request<hyper::Body> {
-        fn from(req: PostHubAccount) -> http::Request<hyper::Body> {
-            post(format!("/participants/{}/accounts", req.name).as_str(), &req.account)
+    #[test]
+    fn positive_amount_serializes_as_the_exact_number() {
+        for (amount, expected) in &[
+            ("1000", "1000"),
+            ("0.1", "0.1"),
+            ("10.25", "10.25"),
+            ("123456789012345678.1234", "123456789012345678.1234"),
+        ] {
+            let amount: PositiveAmount = amount.parse().unwrap();
+            assert_eq!(serde_json::to_string(&amount).unwrap(), *expected);
         }
     }
 
-    impl From<PostCallbackUrl> for http::Request<hyper::Body> {
-        fn from(req: PostCallbackUrl) -> http::Request<hyper::Body> {
-            post(format!("/participants/{}/endpoints", req.name).as_str(), &CallbackUrl {
-                r#type: req.callback_type,
-                value: format!("{}{}", req.hostname, get_callback_path(req.callback_type)),
-            })
-        }
+    #[test]
+    fn positive_amount_from_string_round_trips() {
+        let amount: PositiveAmount = serde_json::from_str("\"123456789012345678.1234\"").unwrap();
+        assert_eq!(serde_json::to_string(&amount).unwrap(), "123456789012345678.1234");
+        let amount: PositiveAmount = serde_json::from_str("\"0.0001\"").unwrap();
+        assert_eq!(serde_json::to_string(&amount).unwrap(), "0.0001");
     }
 
-    impl From<PostParticipantSettlementFunds> for http::Request<hyper::Body> {
-        fn from(req: PostParticipantSettlementFunds) -> http::Request<hyper::Body> {
-            post(format!("/participants/{}/accounts/{}", req.name, req.account_id).as_str(), &req.funds)
+    #[test]
+    fn positive_amount_from_number_is_exact() {
+        for s in &["123456789012345678.1234", "0.1", "10.25", "1000"] {
+            let limit: Limit = serde_json::from_str(
+                &format!(r#"{{"type":"NET_DEBIT_CAP","value":{}}}"#, s)
+            ).unwrap();
+            assert_eq!(serde_json::to_string(&limit.value).unwrap(), *s);
+            assert_eq!(serde_json::to_value(limit.value.amount()).unwrap(), serde_json::json!(s));
         }
+        let amount: PositiveAmount = serde_json::from_value(serde_json::json!(1000)).unwrap();
+        assert_eq!(serde_json::to_string(&amount).unwrap(), "1000");
     }
 
-    impl From<GetParticipants> for http::Request<hyper::Body> {
-        fn from(_req: GetParticipants) -> http::Request<hyper::Body> {
-            get("/participants", &NoBody)
+    #[test]
+    fn positive_amount_rejects_zero() {
+        assert_eq!("0".parse::<PositiveAmount>().unwrap_err(), PositiveAmountError::NotPositive);
+    }
+
+    #[test]
+    fn msisdn_test_vectors() {
+        for valid in &["447700900123", "12345", "123456789012345", "255712345678"] {
+            assert_eq!(Msisdn::new(*valid).unwrap().as_str(), *valid);
+        }
+        for (invalid, err) in &[
+            ("+447700900123", MsisdnError::NotNumeric),
+            ("44 7700 900123", MsisdnError::NotNumeric),
+            ("44770090012a", MsisdnError::NotNumeric),
+            ("", MsisdnError::InvalidLength { min: 5, max: 15 }),
+            ("1234", MsisdnError::InvalidLength { min: 5, max: 15 }),
+            ("1234567890123456", MsisdnError::InvalidLength { min: 5, max: 15 }),
+            ("07700900123", MsisdnError::LeadingZero),
+        ] {
+            assert_eq!(Msisdn::new(*invalid).unwrap_err(), *err, "{}", invalid);
         }
     }
 
-    impl From<GetCallbackUrls> for http::Request<hyper::Body> {
-        fn from(req: GetCallbackUrls) -> http::Request<hyper::Body> {
-            get(format!("/participants/{}/endpoints", req.name).as_str(), &NoBody)
+    #[test]
+    fn iban_test_vectors() {
+        for valid in &[
+            "GB82WEST12345698765432",
+            "DE89370400440532013000",
+            "NL91ABNA0417164300",
+            "FR1420041010050500013M02606",
+            "BE71096123456769",
+        ] {
+            assert_eq!(Iban::new(*valid).unwrap().as_str(), *valid);
+        }
+        for (invalid, err) in &[
+            ("GB82WEST12345698765431", IbanError::InvalidChecksum),
+            ("GB28WEST12345698765432", IbanError::InvalidChecksum),
+            ("DE89370400440532013001", IbanError::InvalidChecksum),
+            ("gb82west12345698765432", IbanError::InvalidCharacters),
+            ("GB82 WEST 1234 5698 7654 32", IbanError::InvalidCharacters),
+            ("GB82WEST", IbanError::InvalidLength { min: 15, max: 34 }),
+            ("GB82WEST123456987654321234567890123", IbanError::InvalidLength { min: 15, max: 34 }),
+            ("1282WEST12345698765432", IbanError::InvalidPrefix),
+            ("GBX2WEST12345698765432", IbanError::InvalidPrefix),
+        ] {
+            assert_eq!(Iban::new(*invalid).unwrap_err(), *err, "{}", invalid);
         }
     }
 
-    impl From<GetDfspAccounts> for http::Request<hyper::Body> {
-        fn from(req: GetDfspAccounts) -> http::Request<hyper::Body> {
-            get(format!("/participants/{}/accounts", req.name).as_str(), &NoBody)
+    #[test]
+    fn email_address_test_vectors() {
+        for valid in &["user@example.com", "first.last+tag@mail.example.co.uk", "a@b.c"] {
+            assert_eq!(EmailAddress::new(*valid).unwrap().as_str(), *valid);
+        }
+        for (invalid, err) in &[
+            ("", EmailAddressError::InvalidFormat),
+            ("user.example.com", EmailAddressError::InvalidFormat),
+            ("@example.com", EmailAddressError::InvalidFormat),
+            ("user@example", EmailAddressError::InvalidFormat),
+            ("user@.example.com", EmailAddressError::InvalidFormat),
+            ("user@example.com.", EmailAddressError::InvalidFormat),
+            ("user@host@example.com", EmailAddressError::InvalidFormat),
+            ("user name@example.com", EmailAddressError::ContainsWhitespace),
+            ("user@example.com\n", EmailAddressError::ContainsWhitespace),
+        ] {
+            assert_eq!(EmailAddress::new(*invalid).unwrap_err(), *err, "{:?}", invalid);
         }
+        let too_long = format!("{}@example.com", "a".repeat(EmailAddress::MAX_LEN));
+        assert_eq!(EmailAddress::new(too_long).unwrap_err(), EmailAddressError::TooLong { max: 254 });
     }
 
-    impl From<PostInitialPositionAndLimits> for http::Request<hyper::Body> {
-        fn from(req: PostInitialPositionAndLimits) -> http::Request<hyper::Body> {
-            post(
-                format!("/participants/{}/initialPositionAndLimits", req.name).as_str(),
-                &req.initial_position_and_limits
-            )
+    #[test]
+    fn party_id_validates_by_type() {
+        let id = PartyId::new(PartyIdType::IBAN, "GB82WEST12345698765432").unwrap();
+        assert_eq!(id.party_id_type(), PartyIdType::IBAN);
+        assert_eq!(
+            serde_json::to_value(&id).unwrap(),
+            serde_json::json!({ "partyIdType": "IBAN", "partyIdentifier": "GB82WEST12345698765432" }),
+        );
+        assert_eq!(
+            PartyId::new(PartyIdType::MSISDN, "+447700900123").unwrap_err(),
+            PartyIdError::Msisdn(MsisdnError::NotNumeric),
+        );
+        assert_eq!(
+            PartyId::new(PartyIdType::EMAIL, "user@example").unwrap_err(),
+            PartyIdError::Email(EmailAddressError::InvalidFormat),
+        );
+        assert_eq!(
+            PartyId::new(PartyIdType::ALIAS, "").unwrap_err(),
+            PartyIdError::Identifier(PartyIdType::ALIAS, StringLengthError::Empty),
+        );
+        assert!(serde_json::from_value::<PartyId>(serde_json::json!({
+            "partyIdType": "IBAN",
+            "partyIdentifier": "GB82WEST12345698765431",
+        })).is_err());
+    }
+
+    #[test]
+    fn callback_endpoint_round_trips_every_type() {
+        let hostname = CallbackHostname::new("http://dfsp.example.com:3000").unwrap();
+        for callback_type in FspiopCallbackType::iter() {
+            let endpoint = CallbackEndpoint { callback_type, hostname: hostname.clone() };
+            let url = CallbackUrl::from(&endpoint);
+            assert_eq!(url.value, format!("http://dfsp.example.com:3000{}", callback_type.path_template()));
+            assert_eq!(CallbackEndpoint::try_from(&url).unwrap(), endpoint, "{}", callback_type);
         }
     }
 
-    impl From<PostParticipant> for http::Request<hyper::Body> {
-        fn from(req: PostParticipant) -> http::Request<hyper::Body> {
-            post("/participants", &req.participant)
+    #[test]
+    fn callback_endpoint_render() {
+        let endpoint = CallbackEndpoint {
+            callback_type: FspiopCallbackType::FspiopCallbackUrlTransferError,
+            hostname: CallbackHostname::new("https://dfsp.example.com/fspiop/").unwrap(),
+        };
+        assert_eq!(endpoint.render(), "https://dfsp.example.com/fspiop/transfers/{{transferId}}/error");
+        assert_eq!(
+            FspiopCallbackType::FspiopCallbackUrlParticipantSubIdPutError.placeholders(),
+            vec!["{{partyIdType}}", "{{partyIdentifier}}", "{{partySubIdOrType}}"],
+        );
+        assert!(FspiopCallbackType::FspiopCallbackUrlQuotes.placeholders().is_empty());
+    }
+
+    #[test]
+    fn callback_endpoint_rejects_a_value_not_matching_the_template() {
+        let url: CallbackUrl = serde_json::from_value(serde_json::json!({
+            "type": "FSPIOP_CALLBACK_URL_TRANSFER_PUT",
+            "value": "http://dfsp.example.com/transfers/{{id}}",
+        })).unwrap();
+        assert!(matches!(
+            CallbackEndpoint::try_from(&url),
+            Err(CallbackEndpointError::TemplateMismatch {
+                callback_type: FspiopCallbackType::FspiopCallbackUrlTransferPut,
+                ..
+            })
+        ));
+        let url = CallbackUrl {
+            r#type: FspiopCallbackType::FspiopCallbackUrlTransferPost,
+            value: "ftp://dfsp.example.com/transfers".to_string(),
+        };
+        assert!(matches!(
+            CallbackEndpoint::try_from(&url),
+            Err(CallbackEndpointError::InvalidHostname(CallbackHostnameError::UnsupportedScheme(_)))
+        ));
+    }
+
+    fn registrations() -> Vec<PostCallbackUrl> {
+        let hostname = CallbackHostname::new("http://dfsp.example.com").unwrap();
+        let overrides = [(
+            FspiopCallbackType::FspiopCallbackUrlQuotes,
+            CallbackHostname::new("http://quotes.example.com").unwrap(),
+        )].iter().cloned().collect();
+        callback_registrations("dfspa".parse().unwrap(), &hostname, &overrides)
+    }
+
+    fn registered(registrations: &[PostCallbackUrl]) -> Vec<Endpoint> {
+        registrations.iter().map(|registration| Endpoint::Callback(registration.body())).collect()
+    }
+
+    #[test]
+    fn callback_registrations_cover_every_type_with_overrides() {
+        let registrations = registrations();
+        assert_eq!(registrations.len(), FspiopCallbackType::iter().count());
+        for registration in &registrations {
+            let expected = match registration.callback_type {
+                FspiopCallbackType::FspiopCallbackUrlQuotes => "http://quotes.example.com",
+                _ => "http://dfsp.example.com",
+            };
+            assert_eq!(registration.hostname.as_str(), expected);
         }
     }
+
+    #[test]
+    fn verify_callbacks_accepts_what_was_registered() {
+        let registrations = registrations();
+        let mut actual = registered(&registrations);
+        actual.push(Endpoint::Other {
+            r#type: "NET_DEBIT_CAP_ADJUSTMENT_EMAIL".to_string(),
+            value: "ops@dfsp.example.com".to_string(),
+        });
+        assert!(verify_callbacks(&registrations, &actual).is_empty());
+    }
+
+    #[test]
+    fn verify_callbacks_reports_missing_and_different_callbacks() {
+        let registrations = registrations();
+        let actual: Vec<Endpoint> = registered(&registrations)
+            .into_iter()
+            .filter_map(|endpoint| match endpoint {
+                Endpoint::Callback(url) => match url.r#type {
+                    FspiopCallbackType::FspiopCallbackUrlTransferPost => None,
+                    FspiopCallbackType::FspiopCallbackUrlTransferPut => Some(CallbackUrl {
+                        value: "http://old.example.com/transfers/{{transferId}}".to_string(),
+                        ..url
+                    }),
+                    _ => Some(url),
+                },
+                _ => None,
+            })
+            .map(Endpoint::Callback)
+            .collect();
+        assert_eq!(verify_callbacks(&registrations, &actual), vec![
+            CallbackMismatch::Missing {
+                callback_type: FspiopCallbackType::FspiopCallbackUrlTransferPost,
+                expected: "http://dfsp.example.com/transfers".to_string(),
+            },
+            CallbackMismatch::Different {
+                callback_type: FspiopCallbackType::FspiopCallbackUrlTransferPut,
+                expected: "http://dfsp.example.com/transfers/{{transferId}}".to_string(),
+                actual: "http://old.example.com/transfers/{{transferId}}".to_string(),
+            },
+        ]);
+        assert_eq!(verify_callbacks(&registrations, &[]).len(), registrations.len());
+    }
+
+    #[test]
+    fn callback_registration_report_is_ok() {
+        let ok = CallbackRegistrationReport::<String> {
+            results: vec![(FspiopCallbackType::FspiopCallbackUrlQuotes, Ok(()))],
+            mismatches: None,
+        };
+        assert!(ok.is_ok());
+        let failed = CallbackRegistrationReport {
+            results: vec![(FspiopCallbackType::FspiopCallbackUrlQuotes, Err("refused".to_string()))],
+            mismatches: Some(Vec::new()),
+        };
+        assert!(!failed.is_ok());
+        let mismatched = CallbackRegistrationReport::<String> {
+            results: Vec::new(),
+            mismatches: Some(vec![CallbackMismatch::Missing {
+                callback_type: FspiopCallbackType::FspiopCallbackUrlQuotes,
+                expected: "http://quotes.example.com".to_string(),
+            }]),
+        };
+        assert!(!mismatched.is_ok());
+    }
+
+    #[test]
+    fn get_endpoints_response_with_callback_email_and_unknown_types() {
+        let endpoints: <GetCallbackUrls as Request>::Response = serde_json::from_value(serde_json::json!([
+            {
+                "type": "FSPIOP_CALLBACK_URL_TRANSFER_POST",
+                "value": "http://dfsp.example.com/transfers",
+            },
+            {
+                "type": "NET_DEBIT_CAP_THRESHOLD_BREACH_EMAIL",
+                "value": "ops@dfsp.example.com",
+            },
+            {
+                "type": "ALARM_NOTIFICATION_URL",
+                "value": "http://alarms.example.com",
+            },
+            {
+                "type": "FSPIOP_CALLBACK_URL_QUOTES",
+                "value": "http://dfsp.example.com",
+            },
+        ])).unwrap();
+        assert_eq!(endpoints.len(), 4);
+
+        let callbacks: Vec<FspiopCallbackType> = callback_urls(&endpoints).map(|url| url.r#type).collect();
+        assert_eq!(callbacks, vec![
+            FspiopCallbackType::FspiopCallbackUrlTransferPost,
+            FspiopCallbackType::FspiopCallbackUrlQuotes,
+        ]);
+
+        let emails: Vec<&EmailEndpoint> = email_endpoints(&endpoints).collect();
+        assert_eq!(emails, vec![&EmailEndpoint {
+            r#type: EmailEndpointType::NetDebitCapThresholdBreachEmail,
+            value: EmailAddress::new("ops@dfsp.example.com").unwrap(),
+        }]);
+
+        assert!(matches!(
+            &endpoints[2],
+            Endpoint::Other { r#type, value }
+                if r#type == "ALARM_NOTIFICATION_URL" && value == "http://alarms.example.com"
+        ));
+    }
+
+    // An email endpoint whose address doesn't validate is still returned, as an unknown endpoint,
+    // rather than failing the whole response
+    #[test]
+    fn email_endpoint_with_an_invalid_address_is_other() {
+        let endpoint: Endpoint = serde_json::from_value(serde_json::json!({
+            "type": "SETTLEMENT_TRANSFER_POSITION_CHANGE_EMAIL",
+            "value": "not an address",
+        })).unwrap();
+        assert!(matches!(endpoint, Endpoint::Other { .. }));
+        assert!(serde_json::from_value::<EmailEndpoint>(serde_json::json!({
+            "type": "SETTLEMENT_TRANSFER_POSITION_CHANGE_EMAIL",
+            "value": "not an address",
+        })).is_err());
+    }
+
+    #[test]
+    fn post_email_endpoint_body() {
+        let req = PostEmailEndpoint {
+            name: "dfspa".parse().unwrap(),
+            email_type: EmailEndpointType::NetDebitCapAdjustmentEmail,
+            address: "ops@dfsp.example.com".parse().unwrap(),
+        };
+        assert_eq!(req.path_and_query().unwrap(), "/participants/dfspa/endpoints");
+        assert_eq!(serde_json::to_value(req.body()).unwrap(), serde_json::json!({
+            "type": "NET_DEBIT_CAP_ADJUSTMENT_EMAIL",
+            "value": "ops@dfsp.example.com",
+        }));
+        assert_eq!(
+            "ops@dfsp".parse::<EmailAddress>().unwrap_err(),
+            EmailAddressError::InvalidFormat,
+        );
+    }
 }
@@ -1,6 +1,11 @@
 use serde::{Serialize, Deserialize};
+use std::convert::TryFrom;
 use derive_more::Display;
 use strum_macros::EnumString;
+use crate::request::{NoBody, impl_request};
+use crate::typescript::ts_type_alias;
+use crate::central_ledger::CentralLedgerRequest;
+use crate::central_ledger::participants::deserialize_is_active;
 
 #[cfg(feature = "typescript_types")]
 use ts_rs::TS;
@@ -58,51 +63,92 @@ pub enum SettlementAccountType {
     InterchangeFeeSettlement,
 }
 
-// TODO: validation, 2-30 alphanum characters
+// 2-30 alphanumeric characters
 // https://github.com/mojaloop/central-ledger/blob/01435fda1d61093b2e20ff2385e8d65393dac640/src/api/interface/swagger.json#L1583
 #[derive(Deserialize, Serialize, Debug, Copy, Clone, Hash, PartialEq, Eq, Display)]
+#[serde(try_from = "String")]
 pub struct SettlementModelName(arrayvec::ArrayString<30>);
 
 impl SettlementModelName {
-    // TODO: can this be compile-time?
-    pub fn from(item: &str) -> Result<Self, arrayvec::CapacityError<&str>> {
-        Ok(SettlementModelName(arrayvec::ArrayString::from(item)?))
+    pub const MIN_LEN: usize = 2;
+    pub const MAX_LEN: usize = 30;
+
+    pub fn from(item: &str) -> Result<Self, ParseSettlementModelNameErr> {
+        if item.len() < Self::MIN_LEN {
+            return Err(ParseSettlementModelNameErr::SettlementModelNameTooShort);
+        }
+        if !item.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return Err(ParseSettlementModelNameErr::SettlementModelNameNotAlphanumeric);
+        }
+        arrayvec::ArrayString::from(item)
+            .map(SettlementModelName)
+            .map_err(|_| ParseSettlementModelNameErr::SettlementModelNameTooLong)
     }
+
+    /// Whether `item` is a valid settlement model name. Usable in const contexts, see
+    /// [`settlement_model_name!`](crate::settlement_model_name).
+    pub const fn is_valid(item: &str) -> bool {
+        let bytes = item.as_bytes();
+        if bytes.len() < Self::MIN_LEN || bytes.len() > Self::MAX_LEN {
+            return false;
+        }
+        let mut i = 0;
+        while i < bytes.len() {
+            if !bytes[i].is_ascii_alphanumeric() {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+}
+
+/// Creates a [`SettlementModelName`] from a string literal, failing to compile if the literal is
+/// not a valid settlement model name.
+///
+/// ```ignore
+/// let name = settlement_model_name!("DEFERREDNET");
+/// ```
+#[macro_export]
+macro_rules! settlement_model_name {
+    ($name:literal) => {{
+        const _: [(); 0] = [(); !$crate::central_ledger::settlement_models::SettlementModelName::is_valid($name) as usize];
+        match $crate::central_ledger::settlement_models::SettlementModelName::from($name) {
+            Ok(name) => name,
+            Err(_) => unreachable!(),
+        }
+    }};
 }
 
 #[derive(Deserialize, Serialize, Debug, Copy, Clone, Hash, PartialEq, Eq, Display)]
 pub enum ParseSettlementModelNameErr {
+    SettlementModelNameTooShort,
     SettlementModelNameTooLong,
+    SettlementModelNameNotAlphanumeric,
 }
 
+impl std::error::Error for ParseSettlementModelNameErr {}
+
 impl core::str::FromStr for SettlementModelName {
     type Err = ParseSettlementModelNameErr;
 
-    // TODO: can this be compile-time for 'static &str?
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        SettlementModelName::from(s).map_err(|_| ParseSettlementModelNameErr::SettlementModelNameTooLong)
+        SettlementModelName::from(s)
     }
 }
 
-#[cfg(feature = "typescript_types")]
-impl TS for SettlementModelName {
-    fn name() -> String {
-        "SettlementModelName".to_string()
-    }
+impl TryFrom<String> for SettlementModelName {
+    type Error = ParseSettlementModelNameErr;
 
-    fn dependencies() -> Vec<(std::any::TypeId, String)> {
-        Vec::new()
-    }
-
-    fn transparent() -> bool { false }
-
-    // TODO: needs to have a size limit somehow. We could perhaps write out a whole class or
-    // interface definition here. Could that work somehow?
-    fn decl() -> String {
-        "type SettlementModelName = string".to_string()
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        SettlementModelName::from(&s)
     }
 }
 
+// TODO: needs to have a size limit somehow. We could perhaps write out a whole class or
+// interface definition here. Could that work somehow?
+ts_type_alias!(SettlementModelName = "string");
+
 #[cfg_attr(feature = "typescript_types", derive(TS))]
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
@@ -118,19 +164,50 @@ pub struct SettlementModel {
     pub currency: fspiox_api::Currency,
 }
 
+/// A settlement model as returned by GET /settlementModels. Note that this differs from
+/// [`SettlementModel`], the body of POST /settlementModels: the settlement account type is
+/// omitted, and a model without a currency applies to every currency that has no model of its own.
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementModelInfo {
+    pub settlement_model_id: u64,
+    pub name: SettlementModelName,
+    #[serde(deserialize_with = "deserialize_is_active")]
+    pub is_active: bool,
+    pub settlement_granularity: SettlementGranularity,
+    pub settlement_interchange: SettlementInterchange,
+    pub settlement_delay: SettlementDelay,
+    pub currency: Option<fspiox_api::Currency>,
+    #[serde(deserialize_with = "deserialize_is_active")]
+    pub require_liquidity_check: bool,
+    // Yes, it's the name, not the ID
+    #[serde(rename = "ledgerAccountTypeId")]
+    pub ledger_account_type: LedgerAccountType,
+    #[serde(deserialize_with = "deserialize_is_active")]
+    pub auto_position_reset: bool,
+}
+
+pub type SettlementModels = Vec<SettlementModelInfo>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct GetSettlementModels {}
+
 #[derive(Debug, Clone, Copy)]
 pub struct PostSettlementModel {
     pub settlement_model: SettlementModel,
 }
 
-#[cfg(feature = "hyper")]
-pub mod requests {
-    use crate::central_ledger::settlement_models::*;
-    use crate::clients::requests::post;
+impl_request! {
+    GetSettlementModels: CentralLedgerRequest,
+    GET "/settlementModels";
+    body: NoBody;
+    response: SettlementModels;
+}
 
-    impl From<PostSettlementModel> for http::Request<hyper::Body> {
-        fn from(req: PostSettlementModel) -> http::Request<hyper::Body> {
-            post("/settlementModels", &req.settlement_model)
-        }
-    }
+impl_request! {
+    PostSettlementModel: CentralLedgerRequest,
+    POST "/settlementModels";
+    body: SettlementModel = |req| req.settlement_model;
+    response: NoBody;
 }
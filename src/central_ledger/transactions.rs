@@ -0,0 +1,153 @@
+use serde::{Serialize, Deserialize};
+use fspiox_api::{CorrelationId, FspId, Money};
+use crate::central_ledger::participants::PartyIdType;
+use crate::request::{NoBody, impl_request};
+use crate::central_ledger::CentralLedgerRequest;
+
+#[cfg(feature = "typescript_types")]
+use ts_rs::TS;
+
+// GET /transactions/{id} returns the FSPIOP Transaction object that the payee FSP encoded in the
+// ILP packet of the transfer with the given ID, decoded by central-ledger. It's the only
+// admin API lookup of an individual transfer; the transfer's state is not exposed by the admin
+// API.
+// https://github.com/mojaloop/central-ledger/blob/01435fda1d61093b2e20ff2385e8d65393dac640/src/api/transactions/handler.js
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransactionScenario {
+    Deposit,
+    Withdrawal,
+    Transfer,
+    Payment,
+    Refund,
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransactionInitiator {
+    Payer,
+    Payee,
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransactionInitiatorType {
+    Consumer,
+    Agent,
+    Business,
+    Device,
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionType {
+    pub scenario: TransactionScenario,
+    pub sub_scenario: Option<String>,
+    pub initiator: TransactionInitiator,
+    pub initiator_type: TransactionInitiatorType,
+    pub balance_of_payments: Option<String>,
+}
+
+// The party identifier is kept as a string rather than one of the validated identifier types in
+// participants, so that looking up a historical transaction doesn't fail on an identifier that
+// the FSPs involved accepted.
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PartyIdInfo {
+    pub party_id_type: PartyIdType,
+    pub party_identifier: String,
+    pub party_sub_id_or_type: Option<String>,
+    pub fsp_id: Option<FspId>,
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Party {
+    pub party_id_info: PartyIdInfo,
+    pub merchant_classification_code: Option<String>,
+    pub name: Option<String>,
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    pub transaction_id: CorrelationId,
+    pub quote_id: CorrelationId,
+    pub payee: Party,
+    pub payer: Party,
+    pub amount: Money,
+    pub transaction_type: TransactionType,
+    pub note: Option<String>,
+}
+
+/// GET /transactions/{id}, where `id` is the transfer ID
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetTransaction {
+    pub id: CorrelationId,
+}
+
+impl_request! {
+    GetTransaction: CentralLedgerRequest,
+    GET "/transactions/{}", id;
+    body: NoBody;
+    response: Transaction;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::Request;
+
+    #[test]
+    fn transaction_fixture() {
+        let transaction: Transaction = serde_json::from_value(serde_json::json!({
+            "transactionId": "85feac2f-39b2-491b-817e-4a03203d4f14",
+            "quoteId": "7c23e80c-d078-4077-8263-2c047876fcf6",
+            "payee": {
+                "partyIdInfo": {
+                    "partyIdType": "MSISDN",
+                    "partyIdentifier": "27713803912",
+                    "fspId": "payeefsp",
+                },
+            },
+            "payer": {
+                "partyIdInfo": {
+                    "partyIdType": "PERSONAL_ID",
+                    "partyIdentifier": "16135551212",
+                    "partySubIdOrType": "DRIVING_LICENSE",
+                    "fspId": "payerfsp",
+                },
+                "name": "Henrik Karlsson",
+            },
+            "amount": { "currency": "USD", "amount": "123.45" },
+            "transactionType": {
+                "scenario": "TRANSFER",
+                "initiator": "PAYER",
+                "initiatorType": "CONSUMER",
+            },
+            "note": "Note sent to Payee.",
+        })).unwrap();
+        assert_eq!(transaction.payee.party_id_info.party_id_type, PartyIdType::MSISDN);
+        assert_eq!(transaction.payee.party_id_info.fsp_id, Some("payeefsp".parse().unwrap()));
+        assert_eq!(transaction.payer.party_id_info.party_sub_id_or_type.as_deref(), Some("DRIVING_LICENSE"));
+        assert_eq!(transaction.payer.name.as_deref(), Some("Henrik Karlsson"));
+        assert_eq!(transaction.transaction_type.scenario, TransactionScenario::Transfer);
+        assert_eq!(transaction.transaction_type.sub_scenario, None);
+        assert_eq!(transaction.amount.currency, fspiox_api::Currency::USD);
+        assert_eq!(crate::amount::to_decimal(&transaction.amount.amount).unwrap(), "123.45".parse().unwrap());
+    }
+
+    #[test]
+    fn get_transaction_path() {
+        let req = GetTransaction { id: "85feac2f-39b2-491b-817e-4a03203d4f14".parse().unwrap() };
+        assert_eq!(req.path_and_query().unwrap(), "/transactions/85feac2f-39b2-491b-817e-4a03203d4f14");
+    }
+}
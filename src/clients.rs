@@ -1,60 +1,223 @@
+// The hyper clients, which connect to a single pod, e.g. through a Kubernetes port-forward, are
+// behind the clients feature. The reqwest clients are behind clients-reqwest and don't need hyper.
+#[cfg(feature = "clients")]
 pub mod central_ledger;
+#[cfg(feature = "clients")]
 pub mod settlement;
+#[cfg(feature = "clients")]
+pub mod account_lookup;
+#[cfg(feature = "clients")]
+pub mod ml_api_adapter;
+#[cfg(feature = "clients")]
+pub mod quoting_service;
+#[cfg(feature = "clients-reqwest")]
+pub mod reqwest;
+mod response;
+#[cfg(feature = "clients")]
 pub use fspiox_api::clients::*;
 
-pub(crate) mod requests {
-    pub fn base<T: serde::Serialize>(
-        path: &str, body: &T, method: http::Method
-    ) -> hyper::Request<hyper::body::Body> {
-        hyper::Request::builder()
-            .uri(path)
-            .header("Accept", "application/json")
-            .header("Content-Type", "application/json")
-            .method(method)
-            .body(hyper::Body::from(serde_json::to_string(body).unwrap()))
-            .unwrap()
+/// An error sending a request with one of the hyper-based clients
+#[cfg(feature = "clients")]
+#[derive(thiserror::Error, Debug)]
+pub enum SendError {
+    #[error("Failed to build request: {0}")]
+    Request(#[from] crate::request::Error),
+    #[error(transparent)]
+    Client(#[from] fspiox_api::clients::Error),
+    #[error("HTTP error: {0}")]
+    Hyper(#[from] hyper::Error),
+    #[error("Mojaloop API error. Status: {status}. Body: {body}")]
+    MojaloopApiError { status: http::StatusCode, body: String },
+    #[error("Expected a response body, but the response was empty")]
+    EmptyResponse,
+    #[error("Failed to deserialize response body: {source}. Body: {body}")]
+    ResponseDeserialization { source: serde_json::Error, body: String },
+}
+
+#[cfg(feature = "clients")]
+impl From<response::ResponseError> for SendError {
+    fn from(e: response::ResponseError) -> SendError {
+        use response::ResponseError;
+        match e {
+            ResponseError::Status { status, body } => SendError::MojaloopApiError { status, body },
+            ResponseError::Deserialization { source, body } =>
+                SendError::ResponseDeserialization { source, body },
+        }
     }
+}
+
+#[cfg(feature = "clients")]
+async fn send_raw<T: crate::request::Request>(
+    sender: &mut hyper::client::conn::SendRequest<hyper::Body>,
+    req: &T,
+) -> std::result::Result<(http::StatusCode, hyper::body::Bytes), SendError> {
+    let req = requests::HyperRequest::new(req)?;
+    std::future::poll_fn(|cx| sender.poll_ready(cx)).await?;
+    let resp = sender.send_request(req.into()).await?;
+    let status = resp.status();
+    Ok((status, hyper::body::to_bytes(resp.into_body()).await?))
+}
+
+// Sends a request and deserializes the response body ourselves, for callers that need the value
+// of the response. See response::read_response.
+#[cfg(feature = "clients")]
+pub(crate) async fn fetch<T: crate::request::Request>(
+    sender: &mut hyper::client::conn::SendRequest<hyper::Body>,
+    req: &T,
+) -> std::result::Result<Option<T::Response>, SendError> {
+    let (status, body) = send_raw(sender, req).await?;
+    Ok(response::read_response(status, &body)?)
+}
+
+// The hub services respond to a health check with status 502 when unhealthy, but with a health
+// check body all the same, so this reads the body whatever the status.
+#[cfg(feature = "clients")]
+pub(crate) async fn health<T>(
+    sender: &mut hyper::client::conn::SendRequest<hyper::Body>,
+    req: T,
+) -> std::result::Result<crate::health::HealthCheck, SendError>
+where
+    T: crate::request::Request<Response = crate::health::HealthCheck>,
+{
+    let (_, body) = send_raw(sender, &req).await?;
+    Ok(response::deserialize_body(&body)?)
+}
 
-    pub fn get<T: serde::Serialize>(path: &str, body: &T) -> hyper::Request<hyper::body::Body> {
-        base(path, body, http::Method::GET)
+#[cfg(feature = "clients")]
+pub mod requests {
+    use crate::request::{Request, Error};
+    use http::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
+
+    /// A [`Request`] that has been validated and serialized, ready to be converted to a hyper
+    /// request. All the fallible parts of building a request happen in [`HyperRequest::new`], so
+    /// the conversion itself cannot fail.
+    #[derive(Debug, Clone)]
+    pub struct HyperRequest {
+        method: http::Method,
+        uri: http::Uri,
+        body: Vec<u8>,
     }
 
-    pub fn post<T: serde::Serialize>(path: &str, body: &T) -> hyper::Request<hyper::body::Body> {
-        base(path, body, http::Method::POST)
+    impl HyperRequest {
+        pub fn new<T: Request>(req: &T) -> Result<HyperRequest, Error> {
+            let uri = http::Uri::builder()
+                .path_and_query(req.path_and_query()?.as_str())
+                .build()?;
+            Ok(HyperRequest { method: T::METHOD, uri, body: req.body_bytes()? })
+        }
     }
 
-    pub fn put<T: serde::Serialize>(path: &str, body: &T) -> hyper::Request<hyper::body::Body> {
-        base(path, body, http::Method::PUT)
+    impl From<HyperRequest> for http::Request<hyper::Body> {
+        fn from(req: HyperRequest) -> http::Request<hyper::Body> {
+            let mut result = http::Request::new(hyper::Body::from(req.body));
+            *result.method_mut() = req.method;
+            *result.uri_mut() = req.uri;
+            let headers = result.headers_mut();
+            headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            result
+        }
     }
 }
 
+#[cfg(feature = "clients-kube")]
 pub mod k8s {
     pub use fspiox_api::clients::k8s::*;
 
-    use super::Result;
+    pub mod params;
+
+    use super::{Result, SendError};
+    use crate::health::HealthCheck;
     use fspiox_api::clients::{transfer, quote, FspiopClient};
-    use crate::clients::{central_ledger, settlement};
+    use crate::clients::{central_ledger, settlement, account_lookup, ml_api_adapter, quoting_service};
 
     // Shadow the fspiox-api implementation
     pub struct Clients {
         pub transfer: transfer::Client,
         pub quote: quote::Client,
+        /// Health checks of the services behind `transfer` and `quote`, over connections of their
+        /// own
+        pub ml_api_adapter: ml_api_adapter::Client,
+        pub quoting_service: quoting_service::Client,
         pub central_ledger: central_ledger::Client,
         pub settlement: settlement::Client,
+        /// An error where no account-lookup-service admin pod could be connected to, as not
+        /// every hub deployment runs one, so this doesn't fail the other clients
+        pub account_lookup: std::result::Result<account_lookup::Client, params::ConnectError>,
     }
 
-    // Shadow the fspiox-api implementation
+    // Shadow the fspiox-api implementation. See params::get_all_from_k8s to override the clients'
+    // K8S_PARAMS.
     pub async fn get_all_from_k8s(
         client: Option<kube::client::Client>,
         namespace: &Option<String>,
     ) -> Result<Clients> {
         let client = ensure_client(client).await?;
-        let (transfer, quote, central_ledger, settlement) = tokio::try_join!(
+        let (
+            transfer, quote, ml_api_adapter, quoting_service, central_ledger, settlement, account_lookup,
+        ) = tokio::try_join!(
             transfer::Client::from_k8s_params(Some(client.clone()), namespace),
             quote::Client::from_k8s_params(Some(client.clone()), namespace),
+            ml_api_adapter::Client::from_k8s_params(Some(client.clone()), namespace),
+            quoting_service::Client::from_k8s_params(Some(client.clone()), namespace),
             central_ledger::Client::from_k8s_params(Some(client.clone()), namespace),
-            settlement::Client::from_k8s_params(Some(client), namespace),
+            settlement::Client::from_k8s_params(Some(client.clone()), namespace),
+            async {
+                let account_lookup = account_lookup::Client::from_k8s_params(Some(client), namespace).await;
+                Ok::<_, fspiox_api::clients::Error>(account_lookup.map_err(params::ConnectError::from))
+            },
         )?;
-        Ok(Clients { transfer, quote, central_ledger, settlement })
+        Ok(Clients {
+            transfer, quote, ml_api_adapter, quoting_service, central_ledger, settlement, account_lookup,
+        })
+    }
+
+    /// The health of each service in [`Clients`]
+    #[derive(Debug)]
+    pub struct HubHealth {
+        pub ml_api_adapter: std::result::Result<HealthCheck, SendError>,
+        pub quoting_service: std::result::Result<HealthCheck, SendError>,
+        pub central_ledger: std::result::Result<HealthCheck, SendError>,
+        pub settlement: std::result::Result<HealthCheck, SendError>,
+        /// None where the account-lookup-service couldn't be connected to; see
+        /// [`Clients::account_lookup`]
+        pub account_lookup: Option<std::result::Result<HealthCheck, SendError>>,
+    }
+
+    impl HubHealth {
+        /// Whether every service checked responded, and reported itself and all its sub-services
+        /// OK
+        pub fn is_ready(&self) -> bool {
+            [
+                Some(&self.ml_api_adapter),
+                Some(&self.quoting_service),
+                Some(&self.central_ledger),
+                Some(&self.settlement),
+                self.account_lookup.as_ref(),
+            ]
+                .iter()
+                .flatten()
+                .all(|health| matches!(health, Ok(health) if health.is_ok()))
+        }
+    }
+
+    /// Checks the health of the hub services concurrently. A test harness can poll this until
+    /// [`HubHealth::is_ready`].
+    pub async fn hub_health(clients: &mut Clients) -> HubHealth {
+        let account_lookup = clients.account_lookup.as_mut().ok();
+        let account_lookup = async move {
+            match account_lookup {
+                Some(client) => Some(client.health().await),
+                None => None,
+            }
+        };
+        let (ml_api_adapter, quoting_service, central_ledger, settlement, account_lookup) = tokio::join!(
+            clients.ml_api_adapter.health(),
+            clients.quoting_service.health(),
+            clients.central_ledger.health(),
+            clients.settlement.health(),
+            account_lookup,
+        );
+        HubHealth { ml_api_adapter, quoting_service, central_ledger, settlement, account_lookup }
     }
 }
@@ -0,0 +1,58 @@
+use hyper::client::conn;
+use hyper::body::Body;
+use fspiox_api::clients::FspiopClient as MojaloopClient;
+use fspiox_api::clients::{request, ResponseBody};
+use crate::clients::requests::HyperRequest;
+use crate::clients::SendError;
+use crate::health::HealthCheck;
+use crate::account_lookup::GetHealth;
+#[cfg(feature = "clients-kube")]
+use fspiox_api::clients::k8s;
+
+pub use crate::account_lookup::AccountLookupRequest;
+
+#[derive(Debug)]
+pub struct Client {
+    sender: conn::SendRequest<Body>,
+}
+
+impl MojaloopClient for Client {
+    #[cfg(feature = "clients-kube")]
+    const K8S_PARAMS: k8s::KubernetesParams =
+        k8s::KubernetesParams {
+            label: "app.kubernetes.io/name=account-lookup-service-admin",
+            container_name: "account-lookup-service-admin",
+            port: k8s::Port::Number(4001),
+        };
+
+    fn from_sender(sender: conn::SendRequest<Body>) -> Client {
+        Client {
+            sender
+        }
+    }
+}
+
+impl Client {
+    pub async fn send<T>(&mut self, msg: T) -> Result<ResponseBody<T::Response>, SendError>
+    where
+        T: AccountLookupRequest,
+    {
+        let req = HyperRequest::new(&msg)?;
+        Ok(request::<HyperRequest, T::Response>(&mut self.sender, req).await?)
+    }
+
+    /// Like [`Client::send`], but returns the deserialized response body, or None where the body
+    /// is empty.
+    pub async fn fetch<T>(&mut self, msg: T) -> Result<Option<T::Response>, SendError>
+    where
+        T: AccountLookupRequest,
+    {
+        crate::clients::fetch(&mut self.sender, &msg).await
+    }
+
+    /// GET the service's health check. Unlike [`Client::send`], this returns the health check when
+    /// the service reports itself unhealthy, too.
+    pub async fn health(&mut self) -> Result<HealthCheck, SendError> {
+        crate::clients::health(&mut self.sender, GetHealth).await
+    }
+}
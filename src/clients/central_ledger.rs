@@ -1,11 +1,22 @@
 use hyper::client::conn;
 use hyper::body::Body;
 use fspiox_api::clients::FspiopClient as MojaloopClient;
-use crate::central_ledger::{settlement_models, participants};
-use fspiox_api::clients::{request, NoBody, ResponseBody};
+use fspiox_api::clients::{request, ResponseBody};
+use crate::clients::requests::HyperRequest;
+use crate::clients::SendError;
+use crate::health::HealthCheck;
+use crate::central_ledger::GetHealth;
+use crate::central_ledger::participants::{
+    callback_registrations, verify_callbacks, CallbackHostname, CallbackRegistrationReport,
+    FspiopCallbackType, GetCallbackUrls,
+};
+use fspiox_api::FspId;
+use std::collections::HashMap;
 #[cfg(feature = "clients-kube")]
 use fspiox_api::clients::k8s;
 
+pub use crate::central_ledger::CentralLedgerRequest;
+
 #[derive(Debug)]
 pub struct Client {
     sender: conn::SendRequest<Body>,
@@ -27,65 +38,55 @@ impl MojaloopClient for Client {
     }
 }
 
-pub trait CentralLedgerRequest {
-    type Response: serde::de::DeserializeOwned;
-}
-
-impl CentralLedgerRequest for participants::GetParticipantLimits {
-    type Response = Vec<participants::NewParticipantLimit>;
-}
-
-impl CentralLedgerRequest for participants::PutParticipantLimit {
-    type Response = NoBody;
-}
-
-impl CentralLedgerRequest for participants::PutParticipantAccount {
-    type Response = NoBody;
-}
-
-impl CentralLedgerRequest for participants::PostHubAccount {
-    type Response = NoBody;
-}
-
-impl CentralLedgerRequest for participants::PostCallbackUrl {
-    type Response = NoBody;
-}
-
-impl CentralLedgerRequest for participants::PostParticipantSettlementFunds {
-    type Response = NoBody;
-}
-
-impl CentralLedgerRequest for participants::GetParticipants {
-    type Response = participants::Participants;
-}
-
-impl CentralLedgerRequest for participants::GetCallbackUrls {
-    type Response = participants::CallbackUrls;
-}
-
-impl CentralLedgerRequest for participants::GetDfspAccounts {
-    type Response = participants::DfspAccounts;
-}
-
-impl CentralLedgerRequest for participants::PostInitialPositionAndLimits {
-    type Response = NoBody;
-}
-
-impl CentralLedgerRequest for participants::PostParticipant {
-    type Response = participants::Participant;
-}
-
-impl CentralLedgerRequest for settlement_models::PostSettlementModel {
-    type Response = NoBody;
-}
-
 impl Client {
-    pub async fn send<T: CentralLedgerRequest>(&mut self, msg: T)
-        -> fspiox_api::clients::Result<ResponseBody<T::Response>>
+    pub async fn send<T>(&mut self, msg: T) -> Result<ResponseBody<T::Response>, SendError>
+    where
+        T: CentralLedgerRequest,
+    {
+        let req = HyperRequest::new(&msg)?;
+        Ok(request::<HyperRequest, T::Response>(&mut self.sender, req).await?)
+    }
+
+    /// Like [`Client::send`], but returns the deserialized response body, or None where the body
+    /// is empty.
+    pub async fn fetch<T>(&mut self, msg: T) -> Result<Option<T::Response>, SendError>
     where
-        T: CentralLedgerRequest + std::fmt::Debug + Clone,
-        http::Request<hyper::Body>: From<T>
+        T: CentralLedgerRequest,
     {
-        request::<T, T::Response>(&mut self.sender, msg).await
+        crate::clients::fetch(&mut self.sender, &msg).await
+    }
+
+    /// Registers every URL callback type for the DFSP `name`, using `hostname` except where
+    /// `overrides` specifies a hostname for a type. When `verify` is set, the stored callbacks are
+    /// then retrieved with [`GetCallbackUrls`] and compared with those registered. Registrations
+    /// are made one at a time, as the client has a single connection; see
+    /// `clients::reqwest::central_ledger::Client::register_callbacks` to make them concurrently.
+    pub async fn register_callbacks(
+        &mut self,
+        name: FspId,
+        hostname: &CallbackHostname,
+        overrides: &HashMap<FspiopCallbackType, CallbackHostname>,
+        verify: bool,
+    ) -> Result<CallbackRegistrationReport<SendError>, SendError> {
+        let registrations = callback_registrations(name, hostname, overrides);
+        let mut results = Vec::with_capacity(registrations.len());
+        for registration in &registrations {
+            let result = crate::clients::fetch(&mut self.sender, registration).await.map(|_| ());
+            results.push((registration.callback_type, result));
+        }
+        let mismatches = if verify {
+            let actual = self.fetch(GetCallbackUrls { name }).await?
+                .ok_or(SendError::EmptyResponse)?;
+            Some(verify_callbacks(&registrations, &actual))
+        } else {
+            None
+        };
+        Ok(CallbackRegistrationReport { results, mismatches })
+    }
+
+    /// GET the service's health check. Unlike [`Client::send`], this returns the health check when
+    /// the service reports itself unhealthy, too.
+    pub async fn health(&mut self) -> Result<HealthCheck, SendError> {
+        crate::clients::health(&mut self.sender, GetHealth).await
     }
 }
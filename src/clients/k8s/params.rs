@@ -0,0 +1,325 @@
+// Runtime-configurable Kubernetes discovery parameters. Each client's K8S_PARAMS are consts
+// matching the Mojaloop Helm charts, but releases are free to use different labels, container
+// names and ports. Here, any of those can be overridden, in code, from a JSON config file or from
+// environment variables, and a client connected with the result.
+
+use serde::{Serialize, Deserialize};
+use std::convert::TryFrom;
+use std::path::Path;
+use fspiox_api::clients::{transfer, quote, FspiopClient};
+use fspiox_api::clients::k8s::{ensure_client, KubernetesParams, Port};
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{Api, ListParams};
+use crate::clients::{central_ledger, settlement, account_lookup, ml_api_adapter, quoting_service};
+use super::Clients;
+
+/// Deserialized from a number, or from a string, which is a port number where numeric and
+/// otherwise a port name. So `3001` and `"3001"` mean the same in a config file, as they do in the
+/// environment.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ServicePort {
+    Number(u16),
+    /// The name of a port of the container
+    Name(String),
+}
+
+impl From<&str> for ServicePort {
+    fn from(s: &str) -> Self {
+        match s.parse() {
+            Ok(number) => ServicePort::Number(number),
+            Err(_) => ServicePort::Name(s.to_string()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ServicePort {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum NumberOrString {
+            Number(u16),
+            String(String),
+        }
+
+        Ok(match NumberOrString::deserialize(deserializer)? {
+            NumberOrString::Number(number) => ServicePort::Number(number),
+            NumberOrString::String(s) => ServicePort::from(s.as_str()),
+        })
+    }
+}
+
+/// The owned, runtime equivalent of [`KubernetesParams`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceParams {
+    /// A label selector identifying the service's pods, e.g.
+    /// `app.kubernetes.io/name=centralledger-service`
+    pub label: String,
+    pub container_name: String,
+    pub port: ServicePort,
+}
+
+impl From<&KubernetesParams> for ServiceParams {
+    fn from(params: &KubernetesParams) -> Self {
+        ServiceParams {
+            label: params.label.to_string(),
+            container_name: params.container_name.to_string(),
+            port: match &params.port {
+                Port::Number(number) => ServicePort::Number(*number),
+                Port::Name(name) => ServicePort::Name(name.to_string()),
+            },
+        }
+    }
+}
+
+/// Overrides for some or all of a service's parameters
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ServiceParamsOverride {
+    pub label: Option<String>,
+    pub container_name: Option<String>,
+    pub port: Option<ServicePort>,
+}
+
+impl ServiceParamsOverride {
+    /// Reads `{prefix}_LABEL`, `{prefix}_CONTAINER_NAME` and `{prefix}_PORT`. A numeric port is a
+    /// port number, anything else a port name.
+    pub fn from_env(prefix: &str) -> ServiceParamsOverride {
+        let var = |name: &str| std::env::var(format!("{}_{}", prefix, name)).ok();
+        ServiceParamsOverride {
+            label: var("LABEL"),
+            container_name: var("CONTAINER_NAME"),
+            port: var("PORT").map(|port| ServicePort::from(port.as_str())),
+        }
+    }
+
+    /// The parameters in `other` where set, otherwise those in `self`
+    pub fn overridden_by(self, other: ServiceParamsOverride) -> ServiceParamsOverride {
+        ServiceParamsOverride {
+            label: other.label.or(self.label),
+            container_name: other.container_name.or(self.container_name),
+            port: other.port.or(self.port),
+        }
+    }
+
+    /// `defaults`, with the parameters set here overridden
+    pub fn apply(&self, defaults: &KubernetesParams) -> ServiceParams {
+        let defaults = ServiceParams::from(defaults);
+        ServiceParams {
+            label: self.label.clone().unwrap_or(defaults.label),
+            container_name: self.container_name.clone().unwrap_or(defaults.container_name),
+            port: self.port.clone().unwrap_or(defaults.port),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to parse config file: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Overrides for the parameters of each of the [`Clients`], e.g. as a config file:
+///
+/// ```json
+/// {
+///   "centralLedger": { "label": "app=my-central-ledger", "port": 3001 },
+///   "settlement": { "containerName": "settlement" }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HubParams {
+    pub transfer: ServiceParamsOverride,
+    pub quote: ServiceParamsOverride,
+    pub central_ledger: ServiceParamsOverride,
+    pub settlement: ServiceParamsOverride,
+    pub account_lookup: ServiceParamsOverride,
+}
+
+impl HubParams {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<HubParams, ConfigError> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    /// Reads overrides from environment variables named `MOJALOOP_K8S_{SERVICE}_{PARAMETER}`,
+    /// where the service is one of `TRANSFER`, `QUOTE`, `CENTRAL_LEDGER`, `SETTLEMENT` or
+    /// `ACCOUNT_LOOKUP` and the parameter one of `LABEL`, `CONTAINER_NAME` or `PORT`. See
+    /// [`ServiceParamsOverride::from_env`].
+    pub fn from_env() -> HubParams {
+        HubParams {
+            transfer: ServiceParamsOverride::from_env("MOJALOOP_K8S_TRANSFER"),
+            quote: ServiceParamsOverride::from_env("MOJALOOP_K8S_QUOTE"),
+            central_ledger: ServiceParamsOverride::from_env("MOJALOOP_K8S_CENTRAL_LEDGER"),
+            settlement: ServiceParamsOverride::from_env("MOJALOOP_K8S_SETTLEMENT"),
+            account_lookup: ServiceParamsOverride::from_env("MOJALOOP_K8S_ACCOUNT_LOOKUP"),
+        }
+    }
+
+    /// The overrides in `other` where set, otherwise those in `self`. E.g. to have the
+    /// environment take precedence over a config file:
+    /// `HubParams::from_file(path)?.overridden_by(HubParams::from_env())`.
+    pub fn overridden_by(self, other: HubParams) -> HubParams {
+        HubParams {
+            transfer: self.transfer.overridden_by(other.transfer),
+            quote: self.quote.overridden_by(other.quote),
+            central_ledger: self.central_ledger.overridden_by(other.central_ledger),
+            settlement: self.settlement.overridden_by(other.settlement),
+            account_lookup: self.account_lookup.overridden_by(other.account_lookup),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConnectError {
+    #[error(transparent)]
+    Client(#[from] fspiox_api::clients::Error),
+    #[error("Kubernetes API error: {0}")]
+    Kube(#[from] kube::Error),
+    #[error("No running pod found with label {0}")]
+    NoPod(String),
+    #[error("Container {container} has no port named {port}")]
+    PortNotFound { container: String, port: String },
+    #[error("Failed to open port-forward to port {port} of pod {pod}")]
+    PortForward { pod: String, port: u16 },
+    #[error("HTTP connection error: {0}")]
+    Hyper(#[from] hyper::Error),
+}
+
+fn resolve_port(pod: &Pod, params: &ServiceParams) -> Result<u16, ConnectError> {
+    let name = match &params.port {
+        ServicePort::Number(number) => return Ok(*number),
+        ServicePort::Name(name) => name,
+    };
+    pod.spec.iter()
+        .flat_map(|spec| spec.containers.iter())
+        .filter(|container| container.name == params.container_name)
+        .flat_map(|container| container.ports.iter().flatten())
+        .find(|port| port.name.as_deref() == Some(name.as_str()))
+        .and_then(|port| u16::try_from(port.container_port).ok())
+        .ok_or_else(|| ConnectError::PortNotFound {
+            container: params.container_name.clone(),
+            port: name.clone(),
+        })
+}
+
+/// As `FspiopClient::from_k8s_params`, but with `params` in place of the client's `K8S_PARAMS`.
+/// Port-forwards to the first running pod matching the label selector.
+// TODO: this duplicates the pod lookup and port-forward in fspiox-api. Replace it with a
+// constructor there taking owned parameters, once fspiox-api has one.
+pub async fn from_k8s_params<C: FspiopClient>(
+    client: Option<kube::client::Client>,
+    namespace: &Option<String>,
+    params: &ServiceParams,
+) -> Result<C, ConnectError> {
+    let client = ensure_client(client).await?;
+    let pods: Api<Pod> = match namespace {
+        Some(namespace) => Api::namespaced(client, namespace),
+        None => Api::default_namespaced(client),
+    };
+    let pod = pods.list(&ListParams::default().labels(&params.label)).await?
+        .items
+        .into_iter()
+        .find(|pod| pod.status.as_ref().and_then(|status| status.phase.as_deref()) == Some("Running"))
+        .ok_or_else(|| ConnectError::NoPod(params.label.clone()))?;
+    let port = resolve_port(&pod, params)?;
+    let pod_name = pod.metadata.name.unwrap_or_default();
+    let mut forwarder = pods.portforward(&pod_name, &[port]).await?;
+    let stream = forwarder.take_stream(port)
+        .ok_or(ConnectError::PortForward { pod: pod_name, port })?;
+    let (sender, connection) = hyper::client::conn::handshake(stream).await?;
+    tokio::spawn(async move {
+        // Keep the port-forward alive as long as the connection
+        let _forwarder = forwarder;
+        let _ = connection.await;
+    });
+    Ok(C::from_sender(sender))
+}
+
+/// As [`get_all_from_k8s`](super::get_all_from_k8s), with each client's `K8S_PARAMS` overridden
+/// by `params`. As there, failing to connect to the account-lookup-service is reported in
+/// [`Clients::account_lookup`] rather than failing the other clients.
+pub async fn get_all_from_k8s(
+    client: Option<kube::client::Client>,
+    namespace: &Option<String>,
+    params: &HubParams,
+) -> Result<Clients, ConnectError> {
+    let client = ensure_client(client).await?;
+    let transfer_params = params.transfer.apply(&transfer::Client::K8S_PARAMS);
+    let quote_params = params.quote.apply(&quote::Client::K8S_PARAMS);
+    let (
+        transfer, quote, ml_api_adapter, quoting_service, central_ledger, settlement, account_lookup,
+    ) = tokio::try_join!(
+        from_k8s_params::<transfer::Client>(Some(client.clone()), namespace, &transfer_params),
+        from_k8s_params::<quote::Client>(Some(client.clone()), namespace, &quote_params),
+        from_k8s_params::<ml_api_adapter::Client>(Some(client.clone()), namespace, &transfer_params),
+        from_k8s_params::<quoting_service::Client>(Some(client.clone()), namespace, &quote_params),
+        from_k8s_params::<central_ledger::Client>(
+            Some(client.clone()), namespace, &params.central_ledger.apply(&central_ledger::Client::K8S_PARAMS)),
+        from_k8s_params::<settlement::Client>(
+            Some(client.clone()), namespace, &params.settlement.apply(&settlement::Client::K8S_PARAMS)),
+        async {
+            let params = params.account_lookup.apply(&account_lookup::Client::K8S_PARAMS);
+            let account_lookup = from_k8s_params::<account_lookup::Client>(Some(client), namespace, &params).await;
+            Ok::<_, ConnectError>(account_lookup)
+        },
+    )?;
+    Ok(Clients {
+        transfer, quote, ml_api_adapter, quoting_service, central_ledger, settlement, account_lookup,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_port_strings_are_port_numbers() {
+        let params: HubParams = serde_json::from_value(serde_json::json!({
+            "centralLedger": { "port": "3001" },
+            "settlement": { "port": 3007 },
+            "accountLookup": { "port": "http-admin" },
+        })).unwrap();
+        assert_eq!(params.central_ledger.port, Some(ServicePort::Number(3001)));
+        assert_eq!(params.settlement.port, Some(ServicePort::Number(3007)));
+        assert_eq!(params.account_lookup.port, Some(ServicePort::Name("http-admin".to_string())));
+        assert_eq!(params.central_ledger.port, Some(ServicePort::from("3001")));
+    }
+
+    #[test]
+    fn ports_serialize_as_numbers_or_names() {
+        assert_eq!(
+            serde_json::to_value(ServicePort::Number(3001)).unwrap(),
+            serde_json::json!(3001),
+        );
+        assert_eq!(
+            serde_json::to_value(ServicePort::Name("http-api".to_string())).unwrap(),
+            serde_json::json!("http-api"),
+        );
+    }
+
+    #[test]
+    fn overrides_take_precedence_where_set() {
+        let file = ServiceParamsOverride {
+            label: Some("app=file".to_string()),
+            container_name: Some("file".to_string()),
+            port: None,
+        };
+        let env = ServiceParamsOverride {
+            label: Some("app=env".to_string()),
+            container_name: None,
+            port: Some(ServicePort::Number(3001)),
+        };
+        assert_eq!(
+            file.overridden_by(env),
+            ServiceParamsOverride {
+                label: Some("app=env".to_string()),
+                container_name: Some("file".to_string()),
+                port: Some(ServicePort::Number(3001)),
+            },
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use hyper::client::conn;
+use hyper::body::Body;
+use fspiox_api::clients::FspiopClient as MojaloopClient;
+use crate::clients::SendError;
+use crate::health::{GetHealth, HealthCheck};
+#[cfg(feature = "clients-kube")]
+use fspiox_api::clients::{k8s, quote};
+
+/// A connection to quoting-service for its health check. The fspiox-api quote client doesn't
+/// expose its connection, so this is a second one, to the same service.
+#[derive(Debug)]
+pub struct Client {
+    sender: conn::SendRequest<Body>,
+}
+
+impl MojaloopClient for Client {
+    #[cfg(feature = "clients-kube")]
+    const K8S_PARAMS: k8s::KubernetesParams = quote::Client::K8S_PARAMS;
+
+    fn from_sender(sender: conn::SendRequest<Body>) -> Client {
+        Client {
+            sender
+        }
+    }
+}
+
+impl Client {
+    /// GET the service's health check, whether or not the service reports itself healthy
+    pub async fn health(&mut self) -> Result<HealthCheck, SendError> {
+        crate::clients::health(&mut self.sender, GetHealth).await
+    }
+}
@@ -0,0 +1,237 @@
+// Clients for the hub admin APIs that talk plain HTTP(S) via reqwest, for use when the hub is
+// reachable over normal ingress rather than through a Kubernetes port-forward. A
+// `::reqwest::Client` pools connections internally, so a single client can be cloned and shared.
+// TLS support is selected with the clients-reqwest-rustls or clients-reqwest-native-tls features.
+
+use url::Url;
+use crate::request::Request;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+    #[error("Failed to build request: {0}")]
+    Request(#[from] crate::request::Error),
+    #[error("HTTP error: {0}")]
+    Http(#[from] ::reqwest::Error),
+    #[error("Mojaloop API error. Status: {status}. Body: {body}")]
+    MojaloopApiError { status: http::StatusCode, body: String },
+    #[error("Expected a response body, but the response was empty")]
+    EmptyResponse,
+    #[error("Failed to deserialize response body: {source}. Body: {body}")]
+    ResponseDeserialization { source: serde_json::Error, body: String },
+}
+
+impl From<super::response::ResponseError> for Error {
+    fn from(e: super::response::ResponseError) -> Error {
+        use super::response::ResponseError;
+        match e {
+            ResponseError::Status { status, body } => Error::MojaloopApiError { status, body },
+            ResponseError::Deserialization { source, body } =>
+                Error::ResponseDeserialization { source, body },
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone)]
+struct Base {
+    http: ::reqwest::Client,
+    base_url: Url,
+}
+
+impl Base {
+    fn new(http: ::reqwest::Client, base_url: &str) -> Result<Base> {
+        Ok(Base { http, base_url: Url::parse(base_url)? })
+    }
+
+    // Returns None when the response has an empty body; see crate::clients::response::read_response
+    async fn request<T: Request>(&self, req: &T) -> Result<Option<T::Response>> {
+        // Concatenate rather than Url::join, so that a base URL with a path prefix (common with
+        // ingress) keeps its prefix.
+        let url = Url::parse(
+            &format!("{}{}", self.base_url.as_str().trim_end_matches('/'), req.path_and_query()?)
+        )?;
+        let resp = self.http
+            .request(T::METHOD, url)
+            .header("Accept", "application/json")
+            .header("Content-Type", "application/json")
+            .body(req.body_bytes()?)
+            .send()
+            .await?;
+        let status = resp.status();
+        let body = resp.bytes().await?;
+        Ok(super::response::read_response(status, &body)?)
+    }
+}
+
+pub mod central_ledger {
+    use super::{Base, Error, Result};
+    use crate::central_ledger::CentralLedgerRequest;
+    use crate::central_ledger::participants::{
+        self, callback_registrations, verify_callbacks, CallbackHostname, FspiopCallbackType,
+        GetCallbackUrls,
+    };
+    use fspiox_api::FspId;
+    use std::collections::HashMap;
+
+    pub type CallbackRegistrationReport = participants::CallbackRegistrationReport<Error>;
+
+    #[derive(Debug, Clone)]
+    pub struct Client {
+        base: Base,
+    }
+
+    impl Client {
+        /// Create a client for the central-ledger admin API at `base_url`, e.g.
+        /// `https://central-ledger.example.com`.
+        pub fn new(base_url: &str) -> Result<Client> {
+            Client::from_reqwest(::reqwest::Client::new(), base_url)
+        }
+
+        /// Create a client from a preconfigured `reqwest::Client`, for control over TLS, timeouts,
+        /// connection pool settings etc.
+        pub fn from_reqwest(http: ::reqwest::Client, base_url: &str) -> Result<Client> {
+            Ok(Client { base: Base::new(http, base_url)? })
+        }
+
+        pub async fn send<T>(&self, msg: T) -> Result<Option<T::Response>>
+        where
+            T: CentralLedgerRequest,
+        {
+            self.base.request(&msg).await
+        }
+
+        /// Registers every URL callback type for the DFSP `name` concurrently, using `hostname`
+        /// except where `overrides` specifies a hostname for a type. When `verify` is set, the
+        /// stored callbacks are then retrieved with [`GetCallbackUrls`] and compared with those
+        /// registered.
+        pub async fn register_callbacks(
+            &self,
+            name: FspId,
+            hostname: &CallbackHostname,
+            overrides: &HashMap<FspiopCallbackType, CallbackHostname>,
+            verify: bool,
+        ) -> Result<CallbackRegistrationReport> {
+            let registrations = callback_registrations(name, hostname, overrides);
+            let results = futures::future::join_all(
+                registrations.iter().map(|registration| async move {
+                    let result = self.base.request(registration).await.map(|_| ());
+                    (registration.callback_type, result)
+                })
+            ).await;
+            let mismatches = if verify {
+                let actual = self.send(GetCallbackUrls { name }).await?
+                    .ok_or(Error::EmptyResponse)?;
+                Some(verify_callbacks(&registrations, &actual))
+            } else {
+                None
+            };
+            Ok(CallbackRegistrationReport { results, mismatches })
+        }
+    }
+}
+
+pub mod settlement {
+    use super::{Base, Error, Result};
+    use crate::settlement::SettlementRequest;
+    use crate::settlement::multi_currency::PlannedSettlement;
+    use crate::settlement::settlement::{PostSettlement, Settlement};
+    #[cfg(feature = "settlement-scheduler")]
+    use crate::settlement::scheduler::BoxFuture;
+    #[cfg(feature = "settlement-scheduler")]
+    use crate::settlement::settlement_windows::{
+        CloseSettlementWindow, GetSettlementWindows, SettlementWindow, SettlementWindowState,
+    };
+
+    #[derive(Debug, Clone)]
+    pub struct Client {
+        base: Base,
+    }
+
+    impl Client {
+        /// Create a client for the central-settlement API at `base_url`, e.g.
+        /// `https://central-settlement.example.com`.
+        pub fn new(base_url: &str) -> Result<Client> {
+            Client::from_reqwest(::reqwest::Client::new(), base_url)
+        }
+
+        /// Create a client from a preconfigured `reqwest::Client`, for control over TLS, timeouts,
+        /// connection pool settings etc.
+        pub fn from_reqwest(http: ::reqwest::Client, base_url: &str) -> Result<Client> {
+            Ok(Client { base: Base::new(http, base_url)? })
+        }
+
+        pub async fn send<T>(&self, msg: T) -> Result<Option<T::Response>>
+        where
+            T: SettlementRequest,
+        {
+            self.base.request(&msg).await
+        }
+
+        /// Creates each planned settlement concurrently, with the given reason. See
+        /// [`crate::settlement::multi_currency::plan`].
+        pub async fn create_settlements<'a>(
+            &self,
+            planned: &'a [PlannedSettlement],
+            reason: &str,
+        ) -> Vec<(&'a PlannedSettlement, Result<Settlement>)> {
+            futures::future::join_all(
+                planned.iter().map(|settlement| async move {
+                    let new_settlement = settlement.new_settlement(reason.to_string());
+                    let result = self.send(PostSettlement { new_settlement }).await
+                        .and_then(|response| response.ok_or(Error::EmptyResponse));
+                    (settlement, result)
+                })
+            ).await
+        }
+    }
+
+    #[cfg(feature = "settlement-scheduler")]
+    impl crate::settlement::scheduler::SettlementWindowClient for Client {
+        type Error = Error;
+
+        fn open_window(&mut self) -> BoxFuture<'_, Result<Option<SettlementWindow>>> {
+            Box::pin(async move {
+                let windows = self.send(GetSettlementWindows::in_state(SettlementWindowState::Open)).await?;
+                Ok(windows.unwrap_or_default().into_iter().next())
+            })
+        }
+
+        fn close_window(&mut self, close: CloseSettlementWindow) -> BoxFuture<'_, Result<()>> {
+            Box::pin(async move { self.send(close).await.map(|_| ()) })
+        }
+    }
+}
+
+pub mod account_lookup {
+    use super::{Base, Result};
+    use crate::account_lookup::AccountLookupRequest;
+
+    #[derive(Debug, Clone)]
+    pub struct Client {
+        base: Base,
+    }
+
+    impl Client {
+        /// Create a client for the account-lookup-service admin API at `base_url`, e.g.
+        /// `https://account-lookup-service-admin.example.com`.
+        pub fn new(base_url: &str) -> Result<Client> {
+            Client::from_reqwest(::reqwest::Client::new(), base_url)
+        }
+
+        /// Create a client from a preconfigured `reqwest::Client`, for control over TLS, timeouts,
+        /// connection pool settings etc.
+        pub fn from_reqwest(http: ::reqwest::Client, base_url: &str) -> Result<Client> {
+            Ok(Client { base: Base::new(http, base_url)? })
+        }
+
+        pub async fn send<T>(&self, msg: T) -> Result<Option<T::Response>>
+        where
+            T: AccountLookupRequest,
+        {
+            self.base.request(&msg).await
+        }
+    }
+}
@@ -0,0 +1,33 @@
+// How a response failed, read the same way by the hyper and reqwest clients, each of which
+// converts it into its own error type
+pub(crate) enum ResponseError {
+    Status { status: http::StatusCode, body: String },
+    Deserialization { source: serde_json::Error, body: String },
+}
+
+pub(crate) fn deserialize_body<T: serde::de::DeserializeOwned>(
+    body: &[u8],
+) -> std::result::Result<T, ResponseError> {
+    serde_json::from_slice(body).map_err(|source| ResponseError::Deserialization {
+        source,
+        body: String::from_utf8_lossy(body).to_string(),
+    })
+}
+
+// Checks the status of a response and deserializes its body. Returns None when the response has
+// an empty body, as many of the admin API endpoints do on success.
+pub(crate) fn read_response<T: serde::de::DeserializeOwned>(
+    status: http::StatusCode,
+    body: &[u8],
+) -> std::result::Result<Option<T>, ResponseError> {
+    if !status.is_success() {
+        return Err(ResponseError::Status {
+            status,
+            body: String::from_utf8_lossy(body).to_string(),
+        });
+    }
+    if body.is_empty() {
+        return Ok(None);
+    }
+    deserialize_body(body).map(Some)
+}
@@ -1,11 +1,22 @@
 use hyper::client::conn;
 use hyper::body::Body;
 use fspiox_api::clients::FspiopClient as MojaloopClient;
-use crate::settlement::{settlement, settlement_windows};
-use fspiox_api::clients::{request, NoBody, ResponseBody};
+use fspiox_api::clients::{request, ResponseBody};
+use crate::clients::requests::HyperRequest;
+use crate::clients::SendError;
+use crate::health::HealthCheck;
+use crate::settlement::GetHealth;
+#[cfg(feature = "settlement-scheduler")]
+use crate::settlement::scheduler::BoxFuture;
+#[cfg(feature = "settlement-scheduler")]
+use crate::settlement::settlement_windows::{
+    CloseSettlementWindow, GetSettlementWindows, SettlementWindow, SettlementWindowState,
+};
 #[cfg(feature = "clients-kube")]
 use fspiox_api::clients::k8s;
 
+pub use crate::settlement::SettlementRequest;
+
 #[derive(Debug)]
 pub struct Client {
     sender: conn::SendRequest<Body>,
@@ -27,37 +38,43 @@ impl MojaloopClient for Client {
     }
 }
 
-pub trait SettlementRequest {
-    type Response: serde::de::DeserializeOwned;
-}
-
-impl SettlementRequest for settlement::PostSettlement {
-    type Response = settlement::Settlement;
-}
+impl Client {
+    pub async fn send<T>(&mut self, msg: T) -> Result<ResponseBody<T::Response>, SendError>
+    where
+        T: SettlementRequest,
+    {
+        let req = HyperRequest::new(&msg)?;
+        Ok(request::<HyperRequest, T::Response>(&mut self.sender, req).await?)
+    }
 
-impl SettlementRequest for settlement::GetSettlements {
-    type Response = settlement::Settlements;
-}
+    /// Like [`Client::send`], but returns the deserialized response body, or None where the body
+    /// is empty.
+    pub async fn fetch<T>(&mut self, msg: T) -> Result<Option<T::Response>, SendError>
+    where
+        T: SettlementRequest,
+    {
+        crate::clients::fetch(&mut self.sender, &msg).await
+    }
 
-impl SettlementRequest for settlement_windows::GetSettlementWindow {
-    type Response = settlement_windows::SettlementWindow;
+    /// GET the service's health check. Unlike [`Client::send`], this returns the health check when
+    /// the service reports itself unhealthy, too.
+    pub async fn health(&mut self) -> Result<HealthCheck, SendError> {
+        crate::clients::health(&mut self.sender, GetHealth).await
+    }
 }
 
-impl SettlementRequest for settlement_windows::GetSettlementWindows {
-    type Response = settlement_windows::SettlementWindows;
-}
+#[cfg(feature = "settlement-scheduler")]
+impl crate::settlement::scheduler::SettlementWindowClient for Client {
+    type Error = SendError;
 
-impl SettlementRequest for settlement_windows::CloseSettlementWindow {
-    type Response = NoBody;
-}
+    fn open_window(&mut self) -> BoxFuture<'_, Result<Option<SettlementWindow>, SendError>> {
+        Box::pin(async move {
+            let windows = self.fetch(GetSettlementWindows::in_state(SettlementWindowState::Open)).await?;
+            Ok(windows.unwrap_or_default().into_iter().next())
+        })
+    }
 
-impl Client {
-    pub async fn send<T: SettlementRequest>(&mut self, msg: T)
-        -> fspiox_api::clients::Result<ResponseBody<T::Response>>
-    where
-        T: SettlementRequest + std::fmt::Debug + Clone,
-        http::Request<hyper::Body>: From<T>
-    {
-        request::<T, T::Response>(&mut self.sender, msg).await
+    fn close_window(&mut self, close: CloseSettlementWindow) -> BoxFuture<'_, Result<(), SendError>> {
+        Box::pin(async move { self.fetch(close).await.map(|_| ()) })
     }
 }
@@ -0,0 +1,172 @@
+// Central-ledger and central-settlement don't agree on a single timestamp format. Depending on
+// the endpoint and hub version we've seen RFC3339 timestamps, JSON-encoded RFC3339 timestamps
+// nested in a string, e.g. { created: "\"2021-01-01T01:23:34Z\"" }, and MySQL-style timestamps
+// without a timezone, e.g. "2021-01-01 01:23:34", which are UTC.
+
+use std::borrow::Cow;
+
+/// Normalizes any of the timestamp formats returned by the hub to RFC3339
+fn normalize(s: &str) -> Cow<'_, str> {
+    let s = s.trim();
+    let s: Cow<str> = if s.starts_with('"') {
+        match serde_json::from_str::<String>(s) {
+            Ok(inner) => Cow::Owned(inner),
+            Err(_) => Cow::Borrowed(s),
+        }
+    } else {
+        Cow::Borrowed(s)
+    };
+    // Anything that isn't YYYY-MM-DD, a ' ' or 'T' separator, then HH:MM:SS at least, is left for
+    // the DateTime parser to reject
+    if s.len() < 19 || !matches!(s.as_bytes()[10], b' ' | b'T') || !s.is_char_boundary(19) {
+        return s;
+    }
+    // Byte 10 is ASCII, so 10 and 11 are char boundaries
+    let (date, separator, time) = (&s[..10], s.as_bytes()[10], &s[11..]);
+    let has_timezone = s[19..].contains(&['Z', 'z', '+', '-'][..]);
+    match (separator, has_timezone) {
+        (b'T', true) => s,
+        (_, true) => Cow::Owned(format!("{}T{}", date, time)),
+        (_, false) => Cow::Owned(format!("{}T{}Z", date, time)),
+    }
+}
+
+/// For `DateTime` fields: `#[serde(with = "crate::datetime::lenient")]`. Deserializes RFC3339,
+/// nested JSON string and MySQL-style timestamps. Serializes as `DateTime` does.
+pub mod lenient {
+    use fspiox_api::DateTime;
+    use serde::{Serialize, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(datetime: &DateTime, serializer: S) -> Result<S::Ok, S::Error> {
+        datetime.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse(&s).map_err(serde::de::Error::custom)
+    }
+
+    pub(crate) fn parse(s: &str) -> Result<DateTime, serde_json::Error> {
+        serde_json::from_value(serde_json::Value::String(super::normalize(s).into_owned()))
+    }
+
+    /// For `Option<DateTime>` fields: `#[serde(default, with = "crate::datetime::lenient::option")]`
+    pub mod option {
+        use fspiox_api::DateTime;
+        use serde::{Serialize, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(datetime: &Option<DateTime>, serializer: S) -> Result<S::Ok, S::Error> {
+            datetime.serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime>, D::Error> {
+            Option::<String>::deserialize(deserializer)?
+                .map(|s| super::parse(&s).map_err(serde::de::Error::custom))
+                .transpose()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::central_ledger::participants::{DfspAccount, Participant};
+    use crate::settlement::settlement::Settlement;
+    use crate::settlement::settlement_windows::SettlementWindow;
+    use serde_json::{json, Value};
+
+    const RFC3339: &str = "2021-01-01T01:23:34Z";
+    const NESTED: &str = "\"2021-01-01T01:23:34Z\"";
+    const MYSQL: &str = "2021-01-01 01:23:34";
+
+    fn expected() -> Value {
+        serde_json::to_value(lenient::parse(RFC3339).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn normalizes_each_form() {
+        assert_eq!(normalize(RFC3339), RFC3339);
+        assert_eq!(normalize(NESTED), RFC3339);
+        assert_eq!(normalize(MYSQL), RFC3339);
+        assert_eq!(normalize("2021-01-01 01:23:34.123"), "2021-01-01T01:23:34.123Z");
+        assert_eq!(normalize("2021-01-01 01:23:34+01:00"), "2021-01-01T01:23:34+01:00");
+        assert_eq!(normalize("2021-01-01T01:23:34.5-05:00"), "2021-01-01T01:23:34.5-05:00");
+    }
+
+    #[test]
+    fn leaves_malformed_input_for_the_parser() {
+        for s in &["", "2021-01-01", "2021-01-01X000000\u{e9}0", "2021-01-01 01:23:3\u{e9}", "\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}"] {
+            assert_eq!(normalize(s), *s);
+            assert!(lenient::parse(s).is_err());
+        }
+    }
+
+    #[test]
+    fn participant_created() {
+        for form in &[RFC3339, NESTED, MYSQL] {
+            let participant: Participant = serde_json::from_value(json!({
+                "name": "dfsp1",
+                "id": "http://central-ledger/participants/dfsp1",
+                "created": form,
+                "isActive": 1,
+                "accounts": [],
+            })).unwrap();
+            assert_eq!(serde_json::to_value(participant.created).unwrap(), expected(), "{}", form);
+        }
+    }
+
+    #[test]
+    fn dfsp_account_changed_date() {
+        for form in &[RFC3339, NESTED, MYSQL] {
+            let account: DfspAccount = serde_json::from_value(json!({
+                "id": 1,
+                "ledgerAccountType": "POSITION",
+                "currency": "USD",
+                "isActive": 1,
+                "value": "0",
+                "reservedValue": "0",
+                "changedDate": form,
+            })).unwrap();
+            assert_eq!(serde_json::to_value(account.changed_date).unwrap(), expected(), "{}", form);
+        }
+    }
+
+    #[test]
+    fn settlement_window_dates() {
+        for form in &[RFC3339, NESTED, MYSQL] {
+            let window: SettlementWindow = serde_json::from_value(json!({
+                "settlementWindowId": 1,
+                "reason": null,
+                "state": "OPEN",
+                "createdDate": form,
+                "changedDate": form,
+            })).unwrap();
+            assert_eq!(serde_json::to_value(window.created_date).unwrap(), expected(), "{}", form);
+            assert_eq!(serde_json::to_value(window.changed_date.as_ref().unwrap()).unwrap(), expected(), "{}", form);
+        }
+    }
+
+    #[test]
+    fn settlement_dates() {
+        for form in &[RFC3339, NESTED, MYSQL] {
+            let settlement: Settlement = serde_json::from_value(json!({
+                "id": 1,
+                "state": "PENDING_SETTLEMENT",
+                "createdDate": form,
+                "changedDate": form,
+                "settlementWindows": [{
+                    "id": 1,
+                    "reason": "close",
+                    "state": "PENDING_SETTLEMENT",
+                    "createdDate": form,
+                    "changedDate": form,
+                }],
+                "participants": [],
+            })).unwrap();
+            assert_eq!(serde_json::to_value(settlement.created_date).unwrap(), expected(), "{}", form);
+            assert_eq!(serde_json::to_value(settlement.changed_date).unwrap(), expected(), "{}", form);
+            let window = &settlement.settlement_windows[0];
+            assert_eq!(serde_json::to_value(window.created_date).unwrap(), expected(), "{}", form);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use serde::{Serialize, Deserialize};
+use fspiox_api::DateTime;
+
+#[cfg(feature = "typescript_types")]
+use ts_rs::TS;
+
+// The health check response common to the hub services, produced by
+// https://github.com/mojaloop/central-services-health. A service responds to GET /health with
+// status 200 when healthy and 502 when not, with this body in both cases.
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HealthStatus {
+    Ok,
+    Down,
+}
+
+/// The dependencies a service reports on. Services report only those they use; e.g.
+/// central-ledger reports `datastore` and `broker`.
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum SubServiceName {
+    Datastore,
+    Broker,
+    Sidecar,
+    Cache,
+    ParticipantEndpointService,
+    #[serde(other)]
+    Other,
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SubServiceHealth {
+    pub name: SubServiceName,
+    pub status: HealthStatus,
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    pub status: HealthStatus,
+    /// Seconds since the service started
+    pub uptime: f64,
+    #[serde(with = "crate::datetime::lenient")]
+    pub start_time: DateTime,
+    pub version_number: String,
+    #[serde(default)]
+    pub services: Vec<SubServiceHealth>,
+}
+
+impl HealthCheck {
+    /// Whether the service and every sub-service it reports on are OK
+    pub fn is_ok(&self) -> bool {
+        self.status == HealthStatus::Ok &&
+            self.services.iter().all(|service| service.status == HealthStatus::Ok)
+    }
+
+    pub fn sub_service(&self, name: SubServiceName) -> Option<HealthStatus> {
+        self.services.iter().find(|service| service.name == name).map(|service| service.status)
+    }
+}
+
+/// A request to the health check of a hub service that fspiox-api has the FSPIOP client for, i.e.
+/// ml-api-adapter or quoting-service. The admin API services have their own `GetHealth`.
+pub trait HealthRequest: crate::request::Request {}
+
+/// GET /health
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct GetHealth;
+
+crate::request::impl_request! {
+    GetHealth: HealthRequest,
+    GET "/health";
+    body: crate::request::NoBody;
+    response: HealthCheck;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::Request;
+
+    #[test]
+    fn health_requests_use_each_services_path() {
+        assert_eq!(GetHealth.path_and_query().unwrap(), "/health");
+        assert_eq!(crate::central_ledger::GetHealth.path_and_query().unwrap(), "/health");
+        assert_eq!(crate::settlement::GetHealth.path_and_query().unwrap(), "/v2/health");
+        assert_eq!(crate::account_lookup::GetHealth.path_and_query().unwrap(), "/health");
+        assert_eq!(GetHealth::METHOD, http::Method::GET);
+    }
+
+    #[test]
+    fn healthy_response() {
+        let health: HealthCheck = serde_json::from_str(r#"{
+            "status": "OK",
+            "uptime": 3600.25,
+            "startTime": "2021-06-01T10:00:00.000Z",
+            "versionNumber": "13.0.0",
+            "services": [
+                { "name": "datastore", "status": "OK" },
+                { "name": "broker", "status": "OK" }
+            ]
+        }"#).unwrap();
+        assert!(health.is_ok());
+        assert_eq!(health.sub_service(SubServiceName::Datastore), Some(HealthStatus::Ok));
+        assert_eq!(health.sub_service(SubServiceName::Sidecar), None);
+    }
+
+    // The body a service responds with, with status 502, when it or a sub-service is down
+    #[test]
+    fn unhealthy_response() {
+        let health: HealthCheck = serde_json::from_str(r#"{
+            "status": "DOWN",
+            "uptime": 1.5,
+            "startTime": "2021-06-01T10:00:00.000Z",
+            "versionNumber": "13.0.0",
+            "services": [
+                { "name": "datastore", "status": "OK" },
+                { "name": "broker", "status": "DOWN" },
+                { "name": "somethingElse", "status": "OK" }
+            ]
+        }"#).unwrap();
+        assert!(!health.is_ok());
+        assert_eq!(health.status, HealthStatus::Down);
+        assert_eq!(health.sub_service(SubServiceName::Broker), Some(HealthStatus::Down));
+        assert_eq!(health.sub_service(SubServiceName::Other), Some(HealthStatus::Ok));
+    }
+
+    #[test]
+    fn a_down_sub_service_is_not_ok() {
+        let health: HealthCheck = serde_json::from_value(serde_json::json!({
+            "status": "OK",
+            "uptime": 10,
+            "startTime": "2021-06-01T10:00:00.000Z",
+            "versionNumber": "11.1.0",
+            "services": [{ "name": "broker", "status": "DOWN" }],
+        })).unwrap();
+        assert!(!health.is_ok());
+    }
+
+    #[test]
+    fn services_default_to_empty() {
+        let health: HealthCheck = serde_json::from_value(serde_json::json!({
+            "status": "OK",
+            "uptime": 10,
+            "startTime": "2021-06-01T10:00:00.000Z",
+            "versionNumber": "11.1.0",
+        })).unwrap();
+        assert!(health.is_ok());
+        assert!(health.services.is_empty());
+    }
+}
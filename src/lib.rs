@@ -1,5 +1,11 @@
+pub mod request;
+mod typescript;
+pub mod datetime;
+pub mod amount;
+pub mod health;
 pub mod central_ledger;
 pub mod settlement;
+pub mod account_lookup;
 pub use fspiox_api;
-#[cfg(feature = "clients")]
+#[cfg(any(feature = "clients", feature = "clients-reqwest"))]
 pub mod clients;
@@ -0,0 +1,226 @@
+use serde::{Serialize, Deserialize};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+
+// The transport-neutral description of a request to one of the hub admin APIs. Each request type
+// knows its method, path, query and body, and the type of its response; turning that into an
+// actual HTTP request is left to the client backends in crate::clients (hyper, reqwest) or to any
+// other transport a user cares to write.
+
+/// The body of a request that has no body, or the response type of a request whose response has
+/// no body.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoBody;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Invalid path segment: {0:?}")]
+    InvalidPathSegment(String),
+    #[error("Failed to serialize query string: {0}")]
+    Query(#[from] serde_urlencoded::ser::Error),
+    #[error("Failed to serialize request body: {0}")]
+    Body(#[from] serde_json::Error),
+    #[error("Invalid request URI or header value: {0}")]
+    Http(#[from] http::Error),
+}
+
+// https://url.spec.whatwg.org/#path-percent-encode-set, plus the path separator, so that a
+// parameter value is always exactly one segment.
+const PATH_SEGMENT_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ').add(b'"').add(b'<').add(b'>').add(b'#')
+    .add(b'?').add(b'`').add(b'{').add(b'}')
+    .add(b'/').add(b'%');
+
+/// Percent-encodes a path parameter value as a single path segment. Empty values, `.` and `..`
+/// are rejected, as they would change the meaning of the path.
+pub fn path_segment(value: &str) -> Result<String, Error> {
+    match value {
+        "" | "." | ".." => Err(Error::InvalidPathSegment(value.to_string())),
+        _ => Ok(utf8_percent_encode(value, PATH_SEGMENT_ENCODE_SET).to_string()),
+    }
+}
+
+/// Serializes query parameters to a query string. Parameters are emitted in field order, `None`
+/// values are omitted, and keys and values are form-urlencoded, so reserved characters such as
+/// `&`, `=` and `+` in values are escaped. Returns `None` where there are no parameters, so that
+/// the path has no trailing `?`.
+pub fn query_string<T: Serialize>(params: &T) -> Result<Option<String>, Error> {
+    let query = serde_urlencoded::to_string(params)?;
+    Ok(if query.is_empty() { None } else { Some(query) })
+}
+
+pub trait Request {
+    /// The type the response body deserializes to
+    type Response: serde::de::DeserializeOwned;
+    /// The type serialized to form the request body
+    type Body: Serialize;
+
+    const METHOD: http::Method;
+
+    /// The request path with its path parameters filled in, e.g. `/participants/somefsp/limits`
+    fn path(&self) -> Result<String, Error>;
+
+    /// The query string, without the leading `?`
+    fn query(&self) -> Result<Option<String>, Error> {
+        Ok(None)
+    }
+
+    fn body(&self) -> Self::Body;
+
+    fn path_and_query(&self) -> Result<String, Error> {
+        Ok(match self.query()? {
+            Some(query) => format!("{}?{}", self.path()?, query),
+            None => self.path()?,
+        })
+    }
+
+    fn body_bytes(&self) -> Result<Vec<u8>, Error> {
+        Ok(serde_json::to_vec(&self.body())?)
+    }
+}
+
+/// Implements [`Request`] and the given service trait for a request type, from a description of
+/// the endpoint. Path parameters are `{}` placeholders in the path, filled in order from the named
+/// fields of the request, each of which is encoded with [`path_segment`]. For example:
+///
+/// ```ignore
+/// impl_request! {
+///     PostInitialPositionAndLimits: CentralLedgerRequest,
+///     POST "/participants/{}/initialPositionAndLimits", name;
+///     body: InitialPositionAndLimits = |req| req.initial_position_and_limits;
+///     response: NoBody;
+/// }
+/// ```
+///
+/// A `query: |req| <expr>;` line may precede `body` for requests with a query string; the
+/// expression's value is serialized with [`query_string`]. A body given only as a type is
+/// constructed with `Default::default()`.
+macro_rules! impl_request {
+    (
+        $name:ty: $service:path,
+        $method:ident $path:literal $(, $param:ident)*;
+        $(query: |$query_req:ident| $query:expr;)?
+        body: $body:ty $(= |$body_req:ident| $body_expr:expr)?;
+        response: $response:ty $(;)?
+    ) => {
+        impl $crate::request::Request for $name {
+            type Response = $response;
+            type Body = $body;
+            const METHOD: ::http::Method = ::http::Method::$method;
+
+            fn path(&self) -> Result<String, $crate::request::Error> {
+                Ok(format!($path $(, $crate::request::path_segment(&self.$param.to_string())?)*))
+            }
+
+            $(
+                fn query(&self) -> Result<Option<String>, $crate::request::Error> {
+                    let $query_req = self;
+                    $crate::request::query_string(&$query)
+                }
+            )?
+
+            $crate::request::impl_request!(@body $body $(, $body_req, $body_expr)?);
+        }
+
+        impl $service for $name {}
+    };
+    (@body $body:ty) => {
+        fn body(&self) -> $body {
+            <$body>::default()
+        }
+    };
+    (@body $body:ty, $req:ident, $body_expr:expr) => {
+        fn body(&self) -> $body {
+            let $req = self;
+            $body_expr
+        }
+    };
+}
+pub(crate) use impl_request;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    trait TestRequest: Request {}
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Params {
+        participant_id: Option<String>,
+        reason: Option<String>,
+    }
+
+    struct GetThing {
+        name: String,
+        params: Params,
+    }
+
+    impl_request! {
+        GetThing: TestRequest,
+        GET "/things/{}", name;
+        query: |req| req.params;
+        body: NoBody;
+        response: NoBody;
+    }
+
+    fn path_and_query<T: TestRequest>(req: &T) -> String {
+        req.path_and_query().unwrap()
+    }
+
+    fn params(participant_id: &str, reason: &str) -> Params {
+        Params { participant_id: Some(participant_id.to_string()), reason: Some(reason.to_string()) }
+    }
+
+    #[test]
+    fn query_string_escapes_reserved_characters() {
+        assert_eq!(
+            query_string(&params("fsp&a=b+c", "a b/c%d")).unwrap().as_deref(),
+            Some("participantId=fsp%26a%3Db%2Bc&reason=a+b%2Fc%25d"),
+        );
+    }
+
+    #[test]
+    fn query_string_is_in_field_order() {
+        let expected = Some("participantId=b&reason=a");
+        for _ in 0..10 {
+            assert_eq!(query_string(&params("b", "a")).unwrap().as_deref(), expected);
+        }
+    }
+
+    #[test]
+    fn query_string_omits_none() {
+        let params = Params { participant_id: None, reason: Some("x".to_string()) };
+        assert_eq!(query_string(&params).unwrap().as_deref(), Some("reason=x"));
+    }
+
+    #[test]
+    fn query_string_is_none_without_parameters() {
+        let params = Params { participant_id: None, reason: None };
+        assert_eq!(query_string(&params).unwrap(), None);
+    }
+
+    #[test]
+    fn path_and_query_encodes_path_parameters_and_query() {
+        let req = GetThing { name: "fsp/1 %&=+".to_string(), params: params("fsp&1", "x=y") };
+        assert_eq!(
+            path_and_query(&req),
+            "/things/fsp%2F1%20%25&=+?participantId=fsp%261&reason=x%3Dy",
+        );
+    }
+
+    #[test]
+    fn path_and_query_has_no_trailing_question_mark_without_parameters() {
+        let req = GetThing {
+            name: "fsp".to_string(),
+            params: Params { participant_id: None, reason: None },
+        };
+        assert_eq!(path_and_query(&req), "/things/fsp");
+    }
+
+    #[test]
+    fn path_segment_rejects_dot_segments_and_empty() {
+        for value in &["", ".", ".."] {
+            assert!(matches!(path_segment(value), Err(Error::InvalidPathSegment(_))));
+        }
+    }
+}
@@ -1,2 +1,22 @@
 pub mod settlement;
 pub mod settlement_windows;
+pub mod aggregates;
+pub mod netting;
+pub mod preview;
+pub mod multi_currency;
+#[cfg(feature = "settlement-scheduler")]
+pub mod scheduler;
+
+/// A request to the central-settlement API
+pub trait SettlementRequest: crate::request::Request {}
+
+/// GET /v2/health
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default)]
+pub struct GetHealth;
+
+crate::request::impl_request! {
+    GetHealth: SettlementRequest,
+    GET "/v2/health";
+    body: crate::request::NoBody;
+    response: crate::health::HealthCheck;
+}
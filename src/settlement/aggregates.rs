@@ -0,0 +1,34 @@
+use serde::{Serialize, Deserialize};
+use fspiox_api::{Amount, Currency, FspId};
+use crate::central_ledger::participants::LedgerAccountType;
+use crate::settlement::settlement_windows::SettlementWindowId;
+
+#[cfg(feature = "typescript_types")]
+use ts_rs::TS;
+
+// Transfer aggregates for settlement windows.
+//
+// When a settlement window is closed, central-settlement aggregates the window's transfers into
+// its settlementWindowContentAggregation table, per participant, currency, ledger account type,
+// and participant role, and POST /v2/settlements computes net settlement amounts from that. But
+// neither the aggregates nor the transfers are exposed by the central-settlement or central-ledger
+// APIs; the content of GET /v2/settlementWindows/{id} has only ledger account type and currency.
+// So these types are to be populated from a source the user has access to, e.g. a read replica of
+// the central-settlement database, a reporting service or a test harness that knows the transfers
+// it made, and used to preview a settlement before creating it.
+
+/// The transfers from one payer to one payee in a settlement window, in one currency and ledger
+/// account type
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferAggregate {
+    pub settlement_window_id: SettlementWindowId,
+    pub payer: FspId,
+    pub payee: FspId,
+    pub currency: Currency,
+    pub ledger_account_type: LedgerAccountType,
+    pub transfer_count: u64,
+    /// The sum of the transfer amounts
+    pub amount: Amount,
+}
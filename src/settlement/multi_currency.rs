@@ -0,0 +1,273 @@
+use serde::{Serialize, Deserialize};
+use fspiox_api::Currency;
+use rust_decimal::Decimal;
+use crate::central_ledger::settlement_models::{SettlementDelay, SettlementModelInfo, SettlementModelName};
+use crate::central_ledger::participants::LedgerAccountType;
+use crate::settlement::preview::ledger_account_type_matches;
+use crate::settlement::settlement::{
+    NewSettlement, Settlement, SettlementId, WindowParametersNewSettlement,
+};
+use crate::settlement::settlement_windows::{SettlementWindow, SettlementWindowId, SettlementWindowState};
+
+// Helpers for hubs with several currencies. A settlement is created for a single settlement
+// model, and a settlement model applies either to one currency, or to every currency that has no
+// model of its own. So settling everything means finding the model for the content of closed
+// windows, per currency and ledger account type, and creating one settlement per model over the
+// windows with content it covers.
+
+/// The active deferred settlement model that applies to window content in `currency` and
+/// `ledger_account_type`, if any: the one for `currency` itself, or failing that one without a
+/// currency.
+pub fn model_for_currency(
+    models: &[SettlementModelInfo],
+    currency: Currency,
+    ledger_account_type: LedgerAccountType,
+) -> Option<&SettlementModelInfo> {
+    let mut deferred = models.iter().filter(|model|
+        model.is_active &&
+        matches!(model.settlement_delay, SettlementDelay::Deferred) &&
+        ledger_account_type_matches(model.ledger_account_type, ledger_account_type)
+    );
+    deferred.clone().find(|model| model.currency == Some(currency))
+        .or_else(|| deferred.find(|model| model.currency.is_none()))
+}
+
+/// A settlement to be created: one settlement model, and the closed windows with content in the
+/// currencies it applies to
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedSettlement {
+    pub settlement_model: SettlementModelName,
+    pub currencies: Vec<Currency>,
+    pub settlement_windows: Vec<SettlementWindowId>,
+}
+
+impl PlannedSettlement {
+    pub fn new_settlement(&self, reason: String) -> NewSettlement {
+        NewSettlement {
+            settlement_model: self.settlement_model,
+            reason,
+            settlement_windows: self.settlement_windows
+                .iter()
+                .map(|id| WindowParametersNewSettlement { id: *id })
+                .collect(),
+        }
+    }
+}
+
+/// The settlements needed to settle every closed window in `windows`, one per settlement model.
+/// Also returns the currencies with closed window content that nothing was planned for, as no
+/// settlement model applies to any of their content.
+pub fn plan(
+    models: &[SettlementModelInfo],
+    windows: &[SettlementWindow],
+) -> (Vec<PlannedSettlement>, Vec<Currency>) {
+    let mut planned: Vec<PlannedSettlement> = Vec::new();
+    let mut unplanned: Vec<Currency> = Vec::new();
+    let closed = windows.iter().filter(|window| window.state == SettlementWindowState::Closed);
+    for window in closed {
+        for content in window.content() {
+            let model = match model_for_currency(models, content.currency_id, content.ledger_account_type) {
+                Some(model) => model,
+                None => {
+                    if !unplanned.contains(&content.currency_id) {
+                        unplanned.push(content.currency_id);
+                    }
+                    continue;
+                }
+            };
+            let settlement = match planned.iter_mut().position(|s| s.settlement_model == model.name) {
+                Some(index) => &mut planned[index],
+                None => {
+                    planned.push(PlannedSettlement {
+                        settlement_model: model.name,
+                        currencies: Vec::new(),
+                        settlement_windows: Vec::new(),
+                    });
+                    planned.last_mut().unwrap()
+                }
+            };
+            if !settlement.currencies.contains(&content.currency_id) {
+                settlement.currencies.push(content.currency_id);
+            }
+            if !settlement.settlement_windows.contains(&window.settlement_window_id) {
+                settlement.settlement_windows.push(window.settlement_window_id);
+            }
+        }
+    }
+    // Content with no model, e.g. in a ledger account type no model covers, doesn't make its
+    // currency unsettleable where other content in the currency is settled
+    let unsettleable = unplanned.into_iter()
+        .filter(|currency| !planned.iter().any(|settlement| settlement.currencies.contains(currency)))
+        .collect();
+    (planned, unsettleable)
+}
+
+/// One currency of a created settlement
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrencySettlementSummary {
+    pub currency: Currency,
+    pub settlement_model: SettlementModelName,
+    pub settlement_id: SettlementId,
+    /// The number of participant accounts settled in this currency
+    pub accounts: usize,
+    /// The sum of the positive net settlement amounts, i.e. the total participants owe
+    pub total_owed: Decimal,
+    /// The sum of the net settlement amounts, which should be zero
+    pub net: Decimal,
+}
+
+/// A per-currency view of created settlements, each paired with the plan it was created from
+pub fn summarize<'a>(
+    settlements: impl IntoIterator<Item = (&'a PlannedSettlement, &'a Settlement)>
+) -> Vec<CurrencySettlementSummary> {
+    let mut summaries: Vec<CurrencySettlementSummary> = Vec::new();
+    for (planned, settlement) in settlements {
+        let accounts = settlement.participants.iter().flat_map(|participant| participant.accounts.iter());
+        for account in accounts {
+            let currency = account.net_settlement_amount.currency;
+            let amount = account.net_settlement_amount.amount;
+            let summary = match summaries.iter_mut().position(|s|
+                s.currency == currency && s.settlement_model == planned.settlement_model
+            ) {
+                Some(index) => &mut summaries[index],
+                None => {
+                    summaries.push(CurrencySettlementSummary {
+                        currency,
+                        settlement_model: planned.settlement_model,
+                        settlement_id: settlement.id,
+                        accounts: 0,
+                        total_owed: Decimal::ZERO,
+                        net: Decimal::ZERO,
+                    });
+                    summaries.last_mut().unwrap()
+                }
+            };
+            summary.accounts += 1;
+            summary.net += amount;
+            if amount > Decimal::ZERO {
+                summary.total_owed += amount;
+            }
+        }
+    }
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(name: &str, currency: Option<&str>, ledger_account_type: &str) -> SettlementModelInfo {
+        serde_json::from_value(serde_json::json!({
+            "settlementModelId": 1,
+            "name": name,
+            "isActive": 1,
+            "settlementGranularity": "NET",
+            "settlementInterchange": "MULTILATERAL",
+            "settlementDelay": "DEFERRED",
+            "currency": currency,
+            "requireLiquidityCheck": 1,
+            "ledgerAccountTypeId": ledger_account_type,
+            "autoPositionReset": 1,
+        })).unwrap()
+    }
+
+    fn window(id: u64, state: &str, content: &[(&str, &str)]) -> SettlementWindow {
+        let content: Vec<_> = content.iter().enumerate().map(|(i, (ledger_account_type, currency))|
+            serde_json::json!({
+                "id": i,
+                "state": state,
+                "ledgerAccountType": ledger_account_type,
+                "currencyId": currency,
+                "createdDate": "2021-06-01T10:00:00.000Z",
+            })
+        ).collect();
+        serde_json::from_value(serde_json::json!({
+            "settlementWindowId": id,
+            "reason": null,
+            "state": state,
+            "createdDate": "2021-06-01T10:00:00.000Z",
+            "content": content,
+        })).unwrap()
+    }
+
+    fn names(planned: &[PlannedSettlement]) -> Vec<String> {
+        planned.iter().map(|settlement| settlement.settlement_model.to_string()).collect()
+    }
+
+    #[test]
+    fn model_for_currency_prefers_the_currency_model_of_the_ledger_account_type() {
+        let models = [
+            model("FEESUSD", Some("USD"), "INTERCHANGE_FEE"),
+            model("DEFERREDNET", None, "POSITION"),
+            model("DEFERREDNETEUR", Some("EUR"), "POSITION"),
+        ];
+        let name = |currency| model_for_currency(&models, currency, LedgerAccountType::Position)
+            .map(|model| model.name.to_string());
+        assert_eq!(name(Currency::USD).as_deref(), Some("DEFERREDNET"));
+        assert_eq!(name(Currency::EUR).as_deref(), Some("DEFERREDNETEUR"));
+        assert!(model_for_currency(&models, Currency::USD, LedgerAccountType::Settlement).is_none());
+    }
+
+    #[test]
+    fn plan_groups_closed_windows_by_model() {
+        let models = [
+            model("DEFERREDNETUSD", Some("USD"), "POSITION"),
+            model("DEFERREDNETEUR", Some("EUR"), "POSITION"),
+        ];
+        let windows = [
+            window(1, "CLOSED", &[("POSITION", "USD"), ("POSITION", "EUR")]),
+            window(2, "CLOSED", &[("POSITION", "USD")]),
+            window(3, "OPEN", &[("POSITION", "EUR")]),
+        ];
+        let (planned, unsettleable) = plan(&models, &windows);
+        assert_eq!(names(&planned), vec!["DEFERREDNETUSD", "DEFERREDNETEUR"]);
+        assert_eq!(planned[0].settlement_windows, vec!["1".parse().unwrap(), "2".parse().unwrap()]);
+        assert_eq!(planned[1].settlement_windows, vec!["1".parse().unwrap()]);
+        assert!(unsettleable.is_empty());
+    }
+
+    #[test]
+    fn plan_only_reports_currencies_with_nothing_planned() {
+        let models = [model("DEFERREDNETUSD", Some("USD"), "POSITION")];
+        let windows = [window(1, "CLOSED", &[("POSITION", "USD"), ("SETTLEMENT", "USD"), ("POSITION", "EUR")])];
+        let (planned, unsettleable) = plan(&models, &windows);
+        assert_eq!(names(&planned), vec!["DEFERREDNETUSD"]);
+        assert_eq!(planned[0].currencies, vec![Currency::USD]);
+        assert_eq!(unsettleable, vec![Currency::EUR]);
+    }
+
+    #[test]
+    fn summarize_totals_signed_amounts() {
+        let planned = PlannedSettlement {
+            settlement_model: "DEFERREDNET".parse().unwrap(),
+            currencies: vec![Currency::USD],
+            settlement_windows: vec!["1".parse().unwrap()],
+        };
+        let account = |id: u64, amount: &str| serde_json::json!({
+            "id": id,
+            "reason": "",
+            "state": "PENDING_SETTLEMENT",
+            "netSettlementAmount": { "amount": amount, "currency": "USD" },
+        });
+        let settlement: Settlement = serde_json::from_value(serde_json::json!({
+            "id": 3,
+            "state": "PENDING_SETTLEMENT",
+            "createdDate": "2021-06-01T10:00:00.000Z",
+            "changedDate": "2021-06-01T10:00:00.000Z",
+            "settlementWindows": [],
+            "participants": [
+                { "id": 1, "accounts": [account(10, "100.5")] },
+                { "id": 2, "accounts": [account(11, "-60.5"), account(12, "-40")] },
+                { "id": 3, "accounts": [account(13, "0")] },
+            ],
+        })).unwrap();
+        let summaries = summarize(vec![(&planned, &settlement)]);
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].currency, Currency::USD);
+        assert_eq!(summaries[0].accounts, 4);
+        assert_eq!(summaries[0].total_owed, "100.5".parse().unwrap());
+        assert!(summaries[0].net.is_zero());
+    }
+}
@@ -0,0 +1,298 @@
+use serde::{Serialize, Deserialize};
+use std::convert::TryFrom;
+use fspiox_api::{Currency, FspId};
+use rust_decimal::Decimal;
+use crate::amount::{to_decimal, AmountError};
+use crate::settlement::aggregates::TransferAggregate;
+
+// Netting of transfers between participants, for each settlement interchange:
+// - gross: every payer pays every payee the full value of its transfers to them
+// - bilateral: each pair of participants settles the difference between what they sent each other
+// - multilateral: each participant settles its net position with the hub
+// Everything is per currency; amounts in different currencies are never netted.
+
+/// Value moved from `payer` to `payee`: a single transfer, or an aggregate of transfers
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Flow {
+    pub payer: FspId,
+    pub payee: FspId,
+    pub currency: Currency,
+    pub amount: Decimal,
+}
+
+impl TryFrom<&TransferAggregate> for Flow {
+    type Error = AmountError;
+
+    fn try_from(aggregate: &TransferAggregate) -> Result<Self, Self::Error> {
+        Ok(Flow {
+            payer: aggregate.payer,
+            payee: aggregate.payee,
+            currency: aggregate.currency,
+            amount: to_decimal(&aggregate.amount)?,
+        })
+    }
+}
+
+/// An amount `payer` must pay `payee`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Obligation {
+    pub payer: FspId,
+    pub payee: FspId,
+    pub currency: Currency,
+    pub amount: Decimal,
+}
+
+/// A participant's multilateral net position: positive where it owes the hub, negative where it's
+/// owed, as central-settlement's net settlement amounts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NetPosition {
+    pub participant: FspId,
+    pub currency: Currency,
+    pub amount: Decimal,
+}
+
+/// Sums flows per payer, payee and currency, without netting opposing flows. Obligations are in
+/// order of first appearance in `flows`.
+pub fn gross<'a>(flows: impl IntoIterator<Item = &'a Flow>) -> Vec<Obligation> {
+    let mut obligations: Vec<Obligation> = Vec::new();
+    for flow in flows {
+        match obligations.iter_mut().find(|o|
+            o.payer == flow.payer && o.payee == flow.payee && o.currency == flow.currency
+        ) {
+            Some(o) => o.amount += flow.amount,
+            None => obligations.push(Obligation {
+                payer: flow.payer,
+                payee: flow.payee,
+                currency: flow.currency,
+                amount: flow.amount,
+            }),
+        }
+    }
+    obligations
+}
+
+/// Nets flows per pair of participants and currency. Each resulting obligation is from the
+/// participant that sent more to the one that sent less; pairs whose flows cancel out have no
+/// obligation.
+pub fn bilateral<'a>(flows: impl IntoIterator<Item = &'a Flow>) -> Vec<Obligation> {
+    let mut obligations: Vec<Obligation> = Vec::new();
+    for flow in flows {
+        let pair = obligations.iter_mut().find(|o|
+            o.currency == flow.currency && (
+                (o.payer == flow.payer && o.payee == flow.payee) ||
+                (o.payer == flow.payee && o.payee == flow.payer)
+            )
+        );
+        match pair {
+            Some(o) if o.payer == flow.payer => o.amount += flow.amount,
+            Some(o) => o.amount -= flow.amount,
+            None => obligations.push(Obligation {
+                payer: flow.payer,
+                payee: flow.payee,
+                currency: flow.currency,
+                amount: flow.amount,
+            }),
+        }
+    }
+    obligations
+        .into_iter()
+        .filter(|o| !o.amount.is_zero())
+        .map(|o| if o.amount.is_sign_negative() {
+            Obligation { payer: o.payee, payee: o.payer, currency: o.currency, amount: -o.amount }
+        } else {
+            o
+        })
+        .collect()
+}
+
+/// Each participant's net position per currency. Positions are in order of first appearance in
+/// `flows`, and include participants whose position is zero.
+pub fn multilateral<'a>(flows: impl IntoIterator<Item = &'a Flow>) -> Vec<NetPosition> {
+    let mut positions: Vec<NetPosition> = Vec::new();
+    for flow in flows {
+        for (participant, amount) in [(flow.payer, flow.amount), (flow.payee, -flow.amount)] {
+            match positions.iter_mut().find(|p| p.participant == participant && p.currency == flow.currency) {
+                Some(p) => p.amount += amount,
+                None => positions.push(NetPosition { participant, currency: flow.currency, amount }),
+            }
+        }
+    }
+    positions
+}
+
+/// The total value that changes hands in one currency under each interchange, for comparing
+/// settlement models. Multilateral is the total owed to the hub.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NettingComparison {
+    pub currency: Currency,
+    pub gross: Decimal,
+    pub bilateral: Decimal,
+    pub multilateral: Decimal,
+    /// The number of payments between participants, or with the hub, each interchange requires
+    pub gross_payments: usize,
+    pub bilateral_payments: usize,
+    pub multilateral_payments: usize,
+}
+
+/// Compares gross, bilateral and multilateral netting of `flows`, per currency
+pub fn compare(flows: &[Flow]) -> Vec<NettingComparison> {
+    let gross = gross(flows);
+    let bilateral = bilateral(flows);
+    let multilateral = multilateral(flows);
+    let mut currencies: Vec<Currency> = Vec::new();
+    for flow in flows {
+        if !currencies.contains(&flow.currency) {
+            currencies.push(flow.currency);
+        }
+    }
+    currencies
+        .into_iter()
+        .map(|currency| {
+            let gross = gross.iter().filter(|o| o.currency == currency);
+            let bilateral = bilateral.iter().filter(|o| o.currency == currency);
+            let multilateral = multilateral.iter()
+                .filter(|p| p.currency == currency && !p.amount.is_zero());
+            NettingComparison {
+                currency,
+                gross: gross.clone().map(|o| o.amount).sum(),
+                bilateral: bilateral.clone().map(|o| o.amount).sum(),
+                multilateral: multilateral.clone()
+                    .filter(|p| p.amount.is_sign_positive())
+                    .map(|p| p.amount)
+                    .sum(),
+                gross_payments: gross.count(),
+                bilateral_payments: bilateral.count(),
+                multilateral_payments: multilateral.count(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(payer: &str, payee: &str, currency: Currency, amount: i64) -> Flow {
+        Flow {
+            payer: payer.parse().unwrap(),
+            payee: payee.parse().unwrap(),
+            currency,
+            amount: Decimal::from(amount),
+        }
+    }
+
+    fn obligation(payer: &str, payee: &str, currency: Currency, amount: i64) -> Obligation {
+        Obligation {
+            payer: payer.parse().unwrap(),
+            payee: payee.parse().unwrap(),
+            currency,
+            amount: Decimal::from(amount),
+        }
+    }
+
+    fn position(participant: &str, currency: Currency, amount: i64) -> NetPosition {
+        NetPosition { participant: participant.parse().unwrap(), currency, amount: Decimal::from(amount) }
+    }
+
+    #[test]
+    fn gross_sums_without_netting() {
+        let flows = [
+            flow("dfspa", "dfspb", Currency::USD, 100),
+            flow("dfspb", "dfspa", Currency::USD, 40),
+            flow("dfspa", "dfspb", Currency::USD, 20),
+        ];
+        assert_eq!(gross(&flows), vec![
+            obligation("dfspa", "dfspb", Currency::USD, 120),
+            obligation("dfspb", "dfspa", Currency::USD, 40),
+        ]);
+    }
+
+    #[test]
+    fn bilateral_opposing_flows_cancel() {
+        let flows = [
+            flow("dfspa", "dfspb", Currency::USD, 100),
+            flow("dfspb", "dfspa", Currency::USD, 100),
+        ];
+        assert!(bilateral(&flows).is_empty());
+    }
+
+    #[test]
+    fn bilateral_flips_direction_when_net_is_negative() {
+        let flows = [
+            flow("dfspa", "dfspb", Currency::USD, 30),
+            flow("dfspb", "dfspa", Currency::USD, 100),
+        ];
+        assert_eq!(bilateral(&flows), vec![obligation("dfspb", "dfspa", Currency::USD, 70)]);
+    }
+
+    #[test]
+    fn currencies_are_never_netted() {
+        let flows = [
+            flow("dfspa", "dfspb", Currency::USD, 100),
+            flow("dfspb", "dfspa", Currency::EUR, 100),
+        ];
+        assert_eq!(bilateral(&flows), vec![
+            obligation("dfspa", "dfspb", Currency::USD, 100),
+            obligation("dfspb", "dfspa", Currency::EUR, 100),
+        ]);
+        assert_eq!(multilateral(&flows), vec![
+            position("dfspa", Currency::USD, 100),
+            position("dfspb", Currency::USD, -100),
+            position("dfspb", Currency::EUR, 100),
+            position("dfspa", Currency::EUR, -100),
+        ]);
+    }
+
+    #[test]
+    fn multilateral_includes_zero_positions() {
+        let flows = [
+            flow("dfspa", "dfspb", Currency::USD, 50),
+            flow("dfspb", "dfspa", Currency::USD, 50),
+        ];
+        assert_eq!(multilateral(&flows), vec![
+            position("dfspa", Currency::USD, 0),
+            position("dfspb", Currency::USD, 0),
+        ]);
+        let comparison = compare(&flows);
+        assert_eq!(comparison[0].multilateral, Decimal::ZERO);
+        assert_eq!(comparison[0].multilateral_payments, 0);
+    }
+
+    #[test]
+    fn multilateral_positions_sum_to_zero() {
+        let flows = [
+            flow("dfspa", "dfspb", Currency::USD, 100),
+            flow("dfspb", "dfspc", Currency::USD, 30),
+            flow("dfspc", "dfspa", Currency::USD, 10),
+        ];
+        let positions = multilateral(&flows);
+        assert_eq!(positions, vec![
+            position("dfspa", Currency::USD, 90),
+            position("dfspb", Currency::USD, -70),
+            position("dfspc", Currency::USD, -20),
+        ]);
+        assert!(positions.iter().map(|p| p.amount).sum::<Decimal>().is_zero());
+    }
+
+    #[test]
+    fn compare_totals_each_interchange() {
+        let flows = [
+            flow("dfspa", "dfspb", Currency::USD, 100),
+            flow("dfspb", "dfspa", Currency::USD, 40),
+            flow("dfspb", "dfspc", Currency::USD, 60),
+        ];
+        assert_eq!(compare(&flows), vec![NettingComparison {
+            currency: Currency::USD,
+            gross: Decimal::from(200),
+            bilateral: Decimal::from(120),
+            multilateral: Decimal::from(60),
+            gross_payments: 3,
+            bilateral_payments: 2,
+            multilateral_payments: 2,
+        }]);
+    }
+}
@@ -0,0 +1,251 @@
+use serde::{Serialize, Deserialize};
+use std::convert::TryFrom;
+use fspiox_api::{Currency, FspId};
+use rust_decimal::Decimal;
+use crate::central_ledger::participants;
+use crate::central_ledger::settlement_models::{
+    LedgerAccountType, SettlementGranularity, SettlementInterchange, SettlementModel,
+    SettlementModelName,
+};
+use crate::amount::AmountError;
+use crate::settlement::aggregates::TransferAggregate;
+use crate::settlement::netting::{self, Flow, Obligation};
+use crate::settlement::settlement::{ParticipantCurrencyId, Settlement};
+use crate::settlement::settlement_windows::SettlementWindowId;
+
+// A dry run of POST /v2/settlements: the net settlement amounts a settlement over a set of
+// windows is expected to produce, computed from the windows' transfer aggregates (see
+// crate::settlement::aggregates for where those come from). Net settlement amounts follow
+// central-settlement's sign convention: positive where the participant owes, negative where it's
+// owed.
+
+/// A participant's expected settlement in the preview currency
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewPosition {
+    pub participant: FspId,
+    pub transfer_count: u64,
+    /// The sum of the transfers the participant sent
+    pub sent: Decimal,
+    /// The sum of the transfers the participant received
+    pub received: Decimal,
+    pub net_settlement_amount: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementPreview {
+    pub settlement_model: SettlementModelName,
+    pub currency: Currency,
+    pub settlement_windows: Vec<SettlementWindowId>,
+    /// Participants with transfers in the windows, in order of first appearance in the aggregates
+    pub positions: Vec<PreviewPosition>,
+    /// The payments participants are expected to make each other directly under bilateral
+    /// interchange; see [`netting::gross`] and [`netting::bilateral`]. Empty for multilateral
+    /// interchange, where every participant settles with the hub.
+    pub obligations: Vec<Obligation>,
+}
+
+// Only the position ledger account type is common to settlement models and window content.
+pub(crate) fn ledger_account_type_matches(model: LedgerAccountType, content: participants::LedgerAccountType) -> bool {
+    matches!(
+        (model, content),
+        (LedgerAccountType::Position, participants::LedgerAccountType::Position)
+    )
+}
+
+/// Computes the expected result of settling `windows` under `model`, from the windows' transfer
+/// aggregates. Aggregates for other windows, currencies or ledger account types are ignored.
+pub fn preview(
+    model: &SettlementModel,
+    windows: &[SettlementWindowId],
+    aggregates: &[TransferAggregate],
+) -> Result<SettlementPreview, AmountError> {
+    let mut positions: Vec<PreviewPosition> = Vec::new();
+    let mut flows: Vec<Flow> = Vec::new();
+
+    let relevant = aggregates.iter().filter(|aggregate|
+        windows.contains(&aggregate.settlement_window_id) &&
+        aggregate.currency == model.currency &&
+        ledger_account_type_matches(model.ledger_account_type, aggregate.ledger_account_type)
+    );
+    for aggregate in relevant {
+        let flow = Flow::try_from(aggregate)?;
+        let amount = flow.amount;
+        for (participant, sent, received) in [
+            (aggregate.payer, amount, Decimal::ZERO),
+            (aggregate.payee, Decimal::ZERO, amount),
+        ] {
+            let index = match positions.iter().position(|p| p.participant == participant) {
+                Some(index) => index,
+                None => {
+                    positions.push(PreviewPosition {
+                        participant,
+                        transfer_count: 0,
+                        sent: Decimal::ZERO,
+                        received: Decimal::ZERO,
+                        net_settlement_amount: Decimal::ZERO,
+                    });
+                    positions.len() - 1
+                }
+            };
+            let position = &mut positions[index];
+            position.transfer_count += aggregate.transfer_count;
+            position.sent += sent;
+            position.received += received;
+            position.net_settlement_amount += sent - received;
+        }
+        flows.push(flow);
+    }
+
+    let obligations = match (model.settlement_interchange, model.settlement_granularity) {
+        (SettlementInterchange::Multilateral, _) => Vec::new(),
+        (SettlementInterchange::Bilateral, SettlementGranularity::Gross) => netting::gross(&flows),
+        (SettlementInterchange::Bilateral, SettlementGranularity::Net) => netting::bilateral(&flows),
+    };
+
+    Ok(SettlementPreview {
+        settlement_model: model.name,
+        currency: model.currency,
+        settlement_windows: windows.to_vec(),
+        positions,
+        obligations,
+    })
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PreviewDifference {
+    /// A participant in the preview has no account in the settlement
+    Missing { participant: FspId, expected: Decimal },
+    /// A settlement account in the preview currency has no counterpart in the preview. Its
+    /// participant is `None` where the account could not be identified.
+    Unexpected { participant: Option<FspId>, actual: Decimal },
+    Different { participant: FspId, expected: Decimal, actual: Decimal },
+}
+
+/// Compares a preview with the settlement actually created. The settlement identifies accounts by
+/// participant currency ID, which is the account `id` returned by GET
+/// /participants/{name}/accounts, so `participant_of` maps those IDs to participant names.
+/// Participants with a zero net settlement amount in the preview may legitimately be absent from
+/// the settlement, so are not reported as missing.
+pub fn diff(
+    preview: &SettlementPreview,
+    actual: &Settlement,
+    participant_of: impl Fn(&ParticipantCurrencyId) -> Option<FspId>,
+) -> Vec<PreviewDifference> {
+    let mut differences = Vec::new();
+    let mut seen = Vec::new();
+    let accounts = actual.participants.iter()
+        .flat_map(|participant| participant.accounts.iter())
+        .filter(|account| account.net_settlement_amount.currency == preview.currency);
+    for account in accounts {
+        let amount = account.net_settlement_amount.amount;
+        let participant = participant_of(&account.id);
+        let expected = participant.and_then(|participant|
+            preview.positions.iter().find(|position| position.participant == participant)
+        );
+        match (participant, expected) {
+            (Some(participant), Some(expected)) => {
+                seen.push(participant);
+                if expected.net_settlement_amount != amount {
+                    differences.push(PreviewDifference::Different {
+                        participant,
+                        expected: expected.net_settlement_amount,
+                        actual: amount,
+                    });
+                }
+            }
+            (_, None) if amount.is_zero() => {}
+            (participant, _) => differences.push(PreviewDifference::Unexpected { participant, actual: amount }),
+        }
+    }
+    differences.extend(
+        preview.positions.iter()
+            .filter(|position| !position.net_settlement_amount.is_zero())
+            .filter(|position| !seen.contains(&position.participant))
+            .map(|position| PreviewDifference::Missing {
+                participant: position.participant,
+                expected: position.net_settlement_amount,
+            })
+    );
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn position(participant: &str, net_settlement_amount: i64) -> PreviewPosition {
+        PreviewPosition {
+            participant: participant.parse().unwrap(),
+            transfer_count: 1,
+            sent: Decimal::ZERO,
+            received: Decimal::ZERO,
+            net_settlement_amount: Decimal::from(net_settlement_amount),
+        }
+    }
+
+    #[test]
+    fn diff_compares_signed_net_settlement_amounts() {
+        let preview = SettlementPreview {
+            settlement_model: "DEFERREDNET".parse().unwrap(),
+            currency: Currency::USD,
+            settlement_windows: vec![],
+            positions: vec![position("payerfsp", 100), position("payeefsp", -100), position("idlefsp", 0)],
+            obligations: vec![],
+        };
+        // payerfsp owes the hub as expected, payeefsp is owed less than expected, and the
+        // account of some other participant is unexpected
+        let settlement: Settlement = serde_json::from_str(r#"{
+            "id": 3,
+            "state": "PENDING_SETTLEMENT",
+            "createdDate": "2021-06-01T10:00:00.000Z",
+            "changedDate": "2021-06-01T10:00:00.000Z",
+            "settlementWindows": [],
+            "participants": [{
+                "id": 1,
+                "accounts": [
+                    {
+                        "id": 10,
+                        "reason": "",
+                        "state": "PENDING_SETTLEMENT",
+                        "netSettlementAmount": { "amount": 100, "currency": "USD" }
+                    },
+                    {
+                        "id": 11,
+                        "reason": "",
+                        "state": "PENDING_SETTLEMENT",
+                        "netSettlementAmount": { "amount": -90.5, "currency": "USD" }
+                    },
+                    {
+                        "id": 12,
+                        "reason": "",
+                        "state": "PENDING_SETTLEMENT",
+                        "netSettlementAmount": { "amount": -9.5, "currency": "USD" }
+                    },
+                    {
+                        "id": 13,
+                        "reason": "",
+                        "state": "PENDING_SETTLEMENT",
+                        "netSettlementAmount": { "amount": -50, "currency": "EUR" }
+                    }
+                ]
+            }]
+        }"#).unwrap();
+        let participant_of: HashMap<ParticipantCurrencyId, FspId> = [(10, "payerfsp"), (11, "payeefsp")]
+            .iter()
+            .map(|(id, participant)| (serde_json::from_value(serde_json::json!(id)).unwrap(), participant.parse().unwrap()))
+            .collect();
+        let differences = diff(&preview, &settlement, |id| participant_of.get(id).copied());
+        assert_eq!(differences, vec![
+            PreviewDifference::Different {
+                participant: "payeefsp".parse().unwrap(),
+                expected: Decimal::from(-100),
+                actual: "-90.5".parse().unwrap(),
+            },
+            PreviewDifference::Unexpected { participant: None, actual: "-9.5".parse().unwrap() },
+        ]);
+    }
+}
@@ -0,0 +1,308 @@
+// Closes the open settlement window on a schedule.
+//
+// Restarts are tolerated without keeping any state of our own: closing a window opens a new one,
+// so the open window having been created at or after a scheduled time means that the closure for
+// that time has been made already. A closure is only made for a scheduled time when the open
+// window was created before it, so repeating the closure for a scheduled time, e.g. after a
+// restart, does nothing.
+//
+// That decision compares the created date the hub gives the open window with scheduled times
+// computed from this host's clock. Where this host's clock is ahead of the hub's, the window
+// opened by a closure looks to have been created before the scheduled time, and a repeated
+// closure would close it too. So the clocks must agree, or the scheduler be given a clock
+// tolerance of at least the difference between them; see Scheduler::with_clock_tolerance.
+
+use chrono::{DateTime as ChronoDateTime, Duration, FixedOffset, Utc};
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use crate::settlement::settlement_windows::{
+    CloseSettlementWindow, SettlementWindow, SettlementWindowCloseState,
+    SettlementWindowClosurePayload, SettlementWindowId,
+};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The central-settlement requests the scheduler makes. Implemented for the hyper and reqwest
+/// settlement clients, where those are enabled.
+pub trait SettlementWindowClient {
+    type Error: std::error::Error + 'static;
+
+    /// The open settlement window, if there is one
+    fn open_window(&mut self) -> BoxFuture<'_, Result<Option<SettlementWindow>, Self::Error>>;
+
+    fn close_window(&mut self, close: CloseSettlementWindow) -> BoxFuture<'_, Result<(), Self::Error>>;
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid schedule: {0}")]
+pub struct ScheduleError(#[from] cron::error::Error);
+
+/// When to close windows: a cron expression, interpreted in a timezone
+#[derive(Debug, Clone)]
+pub struct WindowSchedule {
+    schedule: cron::Schedule,
+    timezone: FixedOffset,
+}
+
+impl WindowSchedule {
+    /// `spec` is a cron expression with a seconds field, e.g. `0 0 */4 * * *` for every four
+    /// hours, or `0 0 17 * * Mon-Fri` for a 17:00 cut-off on weekdays. Times are in `timezone`.
+    pub fn new(spec: &str, timezone: FixedOffset) -> Result<WindowSchedule, ScheduleError> {
+        Ok(WindowSchedule { schedule: cron::Schedule::from_str(spec)?, timezone })
+    }
+
+    /// The first scheduled time after `time`
+    pub fn next_after(&self, time: ChronoDateTime<Utc>) -> Option<ChronoDateTime<Utc>> {
+        self.schedule
+            .after(&time.with_timezone(&self.timezone))
+            .next()
+            .map(|next| next.with_timezone(&Utc))
+    }
+
+    /// The last scheduled time after `from` and no later than `to`
+    pub fn last_between(
+        &self,
+        from: ChronoDateTime<Utc>,
+        to: ChronoDateTime<Utc>,
+    ) -> Option<ChronoDateTime<Utc>> {
+        self.schedule
+            .after(&from.with_timezone(&self.timezone))
+            .map(|time| time.with_timezone(&Utc))
+            .take_while(|time| *time <= to)
+            .last()
+    }
+}
+
+/// Whether the closure scheduled for `scheduled_for` has been made already, given when the open
+/// window was created: if so, the open window is the one that closure opened. A window created
+/// up to `clock_tolerance` before the scheduled time counts, in case this host's clock is ahead
+/// of the hub's.
+pub fn closure_made(
+    open_window_created: ChronoDateTime<Utc>,
+    scheduled_for: ChronoDateTime<Utc>,
+    clock_tolerance: Duration,
+) -> bool {
+    open_window_created >= scheduled_for - clock_tolerance
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CloseError<E: std::error::Error + 'static> {
+    #[error(transparent)]
+    Send(E),
+    #[error("Couldn't read the created date of open settlement window {0}")]
+    UnreadableCreatedDate(SettlementWindowId),
+}
+
+#[derive(Debug)]
+pub enum ClosureOutcome<E: std::error::Error + 'static> {
+    Closed(SettlementWindowId),
+    /// The open window was created at or after the scheduled time, so the closure has been made
+    /// already
+    AlreadyClosed(SettlementWindowId),
+    NoOpenWindow,
+    Failed(CloseError<E>),
+}
+
+#[derive(Debug)]
+pub struct ClosureRecord<E: std::error::Error + 'static> {
+    pub scheduled_for: ChronoDateTime<Utc>,
+    pub outcome: ClosureOutcome<E>,
+}
+
+// DateTime serializes as an RFC 3339 string
+fn to_chrono(time: &fspiox_api::DateTime) -> Option<ChronoDateTime<Utc>> {
+    match serde_json::to_value(time).ok()? {
+        serde_json::Value::String(s) => ChronoDateTime::parse_from_rfc3339(&s)
+            .ok()
+            .map(|time| time.with_timezone(&Utc)),
+        _ => None,
+    }
+}
+
+pub struct Scheduler<C: SettlementWindowClient> {
+    client: C,
+    schedule: WindowSchedule,
+    catch_up: Duration,
+    clock_tolerance: Duration,
+}
+
+impl<C: SettlementWindowClient> Scheduler<C> {
+    /// A closure missed while the scheduler wasn't running is made when it starts, if it was
+    /// scheduled no more than `catch_up` ago. The reqwest client's connection pool reconnects as
+    /// needed, so a scheduler with that client can run indefinitely; the hyper client is tied to
+    /// a single connection.
+    pub fn new(client: C, schedule: WindowSchedule, catch_up: Duration) -> Scheduler<C> {
+        Scheduler { client, schedule, catch_up, clock_tolerance: Duration::zero() }
+    }
+
+    /// Allow for this host's clock being up to `tolerance` ahead of the hub's. A window created
+    /// less than `tolerance` before a scheduled closure is then taken to have been opened by that
+    /// closure, so isn't closed for it. See [`closure_made`].
+    pub fn with_clock_tolerance(mut self, tolerance: Duration) -> Scheduler<C> {
+        self.clock_tolerance = tolerance;
+        self
+    }
+
+    /// Closes the open window for the closure scheduled at `scheduled_for`, unless that closure
+    /// has been made already.
+    pub async fn close_due(&mut self, scheduled_for: ChronoDateTime<Utc>) -> ClosureRecord<C::Error> {
+        let outcome = match self.close_if_opened_before(scheduled_for).await {
+            Ok(outcome) => outcome,
+            Err(e) => ClosureOutcome::Failed(e),
+        };
+        ClosureRecord { scheduled_for, outcome }
+    }
+
+    async fn close_if_opened_before(
+        &mut self,
+        scheduled_for: ChronoDateTime<Utc>,
+    ) -> Result<ClosureOutcome<C::Error>, CloseError<C::Error>> {
+        let window = match self.client.open_window().await.map_err(CloseError::Send)? {
+            Some(window) => window,
+            None => return Ok(ClosureOutcome::NoOpenWindow),
+        };
+        let id = window.settlement_window_id;
+        let created = to_chrono(&window.created_date).ok_or(CloseError::UnreadableCreatedDate(id))?;
+        if closure_made(created, scheduled_for, self.clock_tolerance) {
+            return Ok(ClosureOutcome::AlreadyClosed(id));
+        }
+        self.client.close_window(CloseSettlementWindow {
+            id,
+            payload: SettlementWindowClosurePayload {
+                state: SettlementWindowCloseState::Closed,
+                reason: format!("Scheduled closure for {}", scheduled_for.to_rfc3339()),
+            },
+        }).await.map_err(CloseError::Send)?;
+        Ok(ClosureOutcome::Closed(id))
+    }
+
+    /// Closes windows on schedule until the schedule has no more times, passing the outcome of
+    /// each scheduled closure to `record`.
+    pub async fn run(&mut self, mut record: impl FnMut(ClosureRecord<C::Error>)) {
+        let mut last = Utc::now();
+        if let Some(missed) = self.schedule.last_between(last - self.catch_up, last) {
+            record(self.close_due(missed).await);
+        }
+        while let Some(next) = self.schedule.next_after(last) {
+            if let Ok(wait) = (next - Utc::now()).to_std() {
+                tokio::time::sleep(wait).await;
+            }
+            record(self.close_due(next).await);
+            last = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> ChronoDateTime<Utc> {
+        ChronoDateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn next_after_is_in_the_schedule_timezone() {
+        let schedule = WindowSchedule::new("0 0 17 * * *", FixedOffset::east_opt(3 * 3600).unwrap()).unwrap();
+        assert_eq!(
+            schedule.next_after(utc("2021-06-01T13:59:59Z")),
+            Some(utc("2021-06-01T14:00:00Z")),
+        );
+        assert_eq!(
+            schedule.next_after(utc("2021-06-01T14:00:00Z")),
+            Some(utc("2021-06-02T14:00:00Z")),
+        );
+    }
+
+    #[test]
+    fn last_between_is_the_latest_time_in_range() {
+        let schedule = WindowSchedule::new("0 0 */4 * * *", FixedOffset::east_opt(0).unwrap()).unwrap();
+        assert_eq!(
+            schedule.last_between(utc("2021-06-01T01:00:00Z"), utc("2021-06-01T09:00:00Z")),
+            Some(utc("2021-06-01T08:00:00Z")),
+        );
+        assert_eq!(
+            schedule.last_between(utc("2021-06-01T01:00:00Z"), utc("2021-06-01T08:00:00Z")),
+            Some(utc("2021-06-01T08:00:00Z")),
+        );
+        assert_eq!(
+            schedule.last_between(utc("2021-06-01T08:00:00Z"), utc("2021-06-01T11:59:59Z")),
+            None,
+        );
+    }
+
+    #[test]
+    fn invalid_schedules_are_rejected() {
+        assert!(WindowSchedule::new("every four hours", FixedOffset::east_opt(0).unwrap()).is_err());
+    }
+
+    #[test]
+    fn closure_is_made_once_a_window_opens_at_or_after_the_scheduled_time() {
+        let scheduled_for = utc("2021-06-01T17:00:00Z");
+        let none = Duration::zero();
+        assert!(!closure_made(scheduled_for - Duration::hours(4), scheduled_for, none));
+        assert!(!closure_made(scheduled_for - Duration::seconds(1), scheduled_for, none));
+        assert!(closure_made(scheduled_for, scheduled_for, none));
+        assert!(closure_made(scheduled_for + Duration::seconds(1), scheduled_for, none));
+    }
+
+    #[test]
+    fn clock_tolerance_covers_a_host_clock_ahead_of_the_hub() {
+        let scheduled_for = utc("2021-06-01T17:00:00Z");
+        let tolerance = Duration::seconds(30);
+        assert!(closure_made(scheduled_for - Duration::seconds(20), scheduled_for, tolerance));
+        assert!(!closure_made(scheduled_for - Duration::seconds(40), scheduled_for, tolerance));
+    }
+
+    // A hub with one open window at a time, where closing the open window opens another at
+    // `now`
+    struct FakeHub {
+        open: (u64, &'static str),
+        now: &'static str,
+        closed: Vec<SettlementWindowId>,
+    }
+
+    fn window(id: u64, created: &str) -> SettlementWindow {
+        serde_json::from_value(serde_json::json!({
+            "settlementWindowId": id,
+            "reason": null,
+            "state": "OPEN",
+            "createdDate": created,
+        })).unwrap()
+    }
+
+    impl SettlementWindowClient for FakeHub {
+        type Error = std::io::Error;
+
+        fn open_window(&mut self) -> BoxFuture<'_, Result<Option<SettlementWindow>, Self::Error>> {
+            Box::pin(async move { Ok(Some(window(self.open.0, self.open.1))) })
+        }
+
+        fn close_window(&mut self, close: CloseSettlementWindow) -> BoxFuture<'_, Result<(), Self::Error>> {
+            Box::pin(async move {
+                self.closed.push(close.id);
+                self.open = (self.open.0 + 1, self.now);
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_closures_close_once() {
+        let hub = FakeHub {
+            open: (1, "2021-06-01T13:00:00.000Z"),
+            now: "2021-06-01T17:00:01.000Z",
+            closed: Vec::new(),
+        };
+        let schedule = WindowSchedule::new("0 0 17 * * *", FixedOffset::east_opt(0).unwrap()).unwrap();
+        let mut scheduler = Scheduler::new(hub, schedule, Duration::hours(1));
+        let scheduled_for = utc("2021-06-01T17:00:00Z");
+        let first = scheduler.close_due(scheduled_for).await;
+        assert!(matches!(first.outcome, ClosureOutcome::Closed(id) if id == "1".parse().unwrap()));
+        let repeat = scheduler.close_due(scheduled_for).await;
+        assert!(matches!(repeat.outcome, ClosureOutcome::AlreadyClosed(id) if id == "2".parse().unwrap()));
+        assert_eq!(scheduler.client.closed.len(), 1);
+    }
+}
+
@@ -1,7 +1,14 @@
 use serde::{Serialize, Deserialize};
-use fspiox_api::{Amount, Currency, FspId, DateTime};
+use std::convert::TryFrom;
+use fspiox_api::{Currency, FspId, DateTime};
+use rust_decimal::Decimal;
 use crate::settlement::settlement_windows::{SettlementWindowId, SettlementWindowState, SettlementWindowContent};
+use derive_more::{Display, FromStr};
 use strum_macros::{EnumString, ToString};
+use crate::request::{NoBody, impl_request};
+use crate::typescript::ts_type_alias;
+use crate::settlement::SettlementRequest;
+use crate::central_ledger::settlement_models::SettlementModelName;
 
 #[cfg(feature = "typescript_types")]
 use ts_rs::TS;
@@ -9,23 +16,23 @@ use ts_rs::TS;
 // TODO: is this actually u64? It's likely whatever type MySQL uses as an auto-incrementing
 // integer.
 #[cfg_attr(feature = "typescript_types", derive(TS))]
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, FromStr, Clone, Copy, Display)]
 pub struct SettlementId(u64);
 
 // TODO: is this actually u64? It's likely whatever type MySQL uses as an auto-incrementing
 // integer.
 #[cfg_attr(feature = "typescript_types", derive(TS))]
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, FromStr, Clone, Copy, Display)]
 pub struct ParticipantId(u64);
 
 // TODO: is this actually u64? It's likely whatever type MySQL uses as an auto-incrementing
 // integer.
 #[cfg_attr(feature = "typescript_types", derive(TS))]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ParticipantCurrencyId(u64);
 
 #[cfg_attr(feature = "typescript_types", derive(TS))]
-#[derive(Serialize, Deserialize, Debug, EnumString, ToString, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, EnumString, ToString, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 pub enum SettlementState {
@@ -38,6 +45,23 @@ pub enum SettlementState {
     Aborted,
 }
 
+impl SettlementState {
+    /// The state a settlement account in this state moves to next. Central-settlement only accepts
+    /// account state changes in this order. None where the account is settled or aborted.
+    /// `Settling` is only ever the state of a settlement, while some of its accounts are settled
+    /// and others not, so it has no next state either.
+    pub fn next(self) -> Option<SettlementState> {
+        use SettlementState::*;
+        match self {
+            PendingSettlement => Some(PsTransfersRecorded),
+            PsTransfersRecorded => Some(PsTransfersReserved),
+            PsTransfersReserved => Some(PsTransfersCommitted),
+            PsTransfersCommitted => Some(Settled),
+            Settling | Settled | Aborted => None,
+        }
+    }
+}
+
 #[cfg_attr(feature = "typescript_types", derive(TS))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -51,7 +75,9 @@ pub struct SettlementSettlementWindow {
     pub id: SettlementWindowId,
     pub reason: Option<String>,
     pub state: SettlementWindowState,
+    #[serde(with = "crate::datetime::lenient")]
     pub created_date: DateTime,
+    #[serde(default, with = "crate::datetime::lenient::option")]
     pub changed_date: Option<DateTime>,
     pub content: Option<Vec<SettlementWindowContent>>,
 }
@@ -60,7 +86,11 @@ pub struct SettlementSettlementWindow {
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub struct NetSettlementAmount {
-    pub amount: Amount,
+    /// Positive where the participant owes the hub, negative where the hub owes the participant.
+    /// Unlike [`fspiox_api::Amount`], which can't be negative, this is a JSON number.
+    #[serde(with = "crate::amount::number")]
+    #[cfg_attr(feature = "typescript_types", ts(type = "number"))]
+    pub amount: Decimal,
     pub currency: Currency,
 }
 
@@ -93,16 +123,18 @@ pub struct Settlement {
     // TODO: not in spec
     // https://github.com/mojaloop/central-settlement/blob/15d42ce259b3c1c57e81874c40ab5f5fb0981c6e/src/interface/swagger.json#L1202
     // Raise issue
+    #[serde(with = "crate::datetime::lenient")]
     pub created_date: DateTime,
+    #[serde(with = "crate::datetime::lenient")]
     pub changed_date: DateTime,
     pub settlement_windows: Vec<SettlementSettlementWindow>,
     pub participants: Vec<SettlementParticipant>,
 }
 
 #[cfg_attr(feature = "typescript_types", derive(TS))]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
-pub struct GetSettlements {
+pub struct SettlementsFilter {
     pub currency: Option<Currency>,
     pub participant_id: Option<FspId>,
     pub settlement_window_id: Option<SettlementWindowId>,
@@ -116,6 +148,63 @@ pub struct GetSettlements {
     // Spec: https://github.com/mojaloop/central-settlement/blob/15d42ce259b3c1c57e81874c40ab5f5fb0981c6e/src/interface/swagger.json#L267
 }
 
+impl SettlementsFilter {
+    pub fn is_empty(&self) -> bool {
+        self.currency.is_none() &&
+        self.participant_id.is_none() &&
+        self.settlement_window_id.is_none() &&
+        self.state.is_none() &&
+        self.from_date_time.is_none() &&
+        self.to_date_time.is_none() &&
+        self.from_settlement_window_date_time.is_none() &&
+        self.to_settlement_window_date_time.is_none()
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy)]
+#[error("At least one GET /settlements query parameter is required")]
+pub struct EmptySettlementsFilter;
+
+/// GET /v2/settlements. Central-settlement requires at least one query parameter, so this can
+/// only be constructed from a non-empty [`SettlementsFilter`], with `GetSettlements::try_from`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "SettlementsFilter", into = "SettlementsFilter")]
+pub struct GetSettlements(SettlementsFilter);
+
+impl GetSettlements {
+    /// The settlements in `state`. A filter with a state is never empty, so this can't fail.
+    pub fn in_state(state: SettlementState) -> GetSettlements {
+        GetSettlements(SettlementsFilter {
+            state: Some(state),
+            ..SettlementsFilter::default()
+        })
+    }
+
+    pub fn filter(&self) -> &SettlementsFilter {
+        &self.0
+    }
+}
+
+impl TryFrom<SettlementsFilter> for GetSettlements {
+    type Error = EmptySettlementsFilter;
+
+    fn try_from(filter: SettlementsFilter) -> Result<Self, Self::Error> {
+        if filter.is_empty() {
+            Err(EmptySettlementsFilter)
+        } else {
+            Ok(GetSettlements(filter))
+        }
+    }
+}
+
+impl From<GetSettlements> for SettlementsFilter {
+    fn from(item: GetSettlements) -> Self {
+        item.0
+    }
+}
+
+ts_type_alias!(GetSettlements = "SettlementsFilter", SettlementsFilter);
+
 pub type Settlements = Vec<Settlement>;
 
 #[cfg_attr(feature = "typescript_types", derive(TS))]
@@ -129,8 +218,7 @@ pub struct WindowParametersNewSettlement {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct NewSettlement {
-    /// The name of the settlement model
-    pub settlement_model: String,
+    pub settlement_model: SettlementModelName,
     pub reason: String,
     pub settlement_windows: Vec<WindowParametersNewSettlement>,
 }
@@ -142,51 +230,248 @@ pub struct PostSettlement {
     pub new_settlement: NewSettlement,
 }
 
-#[cfg(feature = "hyper")]
-pub mod requests {
-    use crate::settlement::settlement::*;
-    use crate::clients::requests::{get, post};
-    use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
-    use itertools::Itertools;
-    use fspiox_api::clients::NoBody;
+impl_request! {
+    PostSettlement: SettlementRequest,
+    POST "/v2/settlements";
+    body: NewSettlement = |req| req.new_settlement.clone();
+    response: Settlement;
+}
 
-    // https://url.spec.whatwg.org/#query-percent-encode-set
-    const QUERY_ENCODE_SET: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'<').add(b'>').add(b'#');
+impl_request! {
+    GetSettlements: SettlementRequest,
+    GET "/v2/settlements";
+    query: |req| req.filter();
+    body: NoBody;
+    response: Settlements;
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSettlement {
+    pub id: SettlementId,
+}
+
+impl_request! {
+    GetSettlement: SettlementRequest,
+    GET "/v2/settlements/{}", id;
+    body: NoBody;
+    response: Settlement;
+}
 
-    impl From<PostSettlement> for http::Request<hyper::Body> {
-        fn from(req: PostSettlement) -> http::Request<hyper::Body> {
-            post("/v2/settlements", &req.new_settlement)
+/// A settlement restricted to a single participant, as returned by GET
+/// /v2/settlements/{id}/participants/{participantId}. Unlike [`Settlement`], it has no created or
+/// changed date.
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantSettlement {
+    pub id: SettlementId,
+    pub state: SettlementState,
+    pub settlement_windows: Vec<SettlementSettlementWindow>,
+    pub participants: Vec<SettlementParticipant>,
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSettlementParticipant {
+    pub id: SettlementId,
+    pub participant_id: ParticipantId,
+}
+
+impl_request! {
+    GetSettlementParticipant: SettlementRequest,
+    GET "/v2/settlements/{}/participants/{}", id, participant_id;
+    body: NoBody;
+    response: ParticipantSettlement;
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementAccountUpdate {
+    pub id: ParticipantCurrencyId,
+    pub state: SettlementState,
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_reference: Option<String>,
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementParticipantUpdate {
+    pub id: ParticipantId,
+    pub accounts: Vec<SettlementAccountUpdate>,
+}
+
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementUpdate {
+    pub participants: Vec<SettlementParticipantUpdate>,
+}
+
+/// PUT /v2/settlements/{id}, to change the state of some or all of a settlement's accounts
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PutSettlement {
+    pub id: SettlementId,
+    pub update: SettlementUpdate,
+}
+
+impl PutSettlement {
+    /// Moves every account of `settlement` that isn't settled or aborted to its next state, with
+    /// the given reason. See [`SettlementState::next`]. None where there's no such account.
+    pub fn advance(settlement: &Settlement, reason: &str) -> Option<PutSettlement> {
+        let participants: Vec<SettlementParticipantUpdate> = settlement.participants
+            .iter()
+            .map(|participant| SettlementParticipantUpdate {
+                id: participant.id,
+                accounts: participant.accounts
+                    .iter()
+                    .filter_map(|account| account.state.next().map(|state| SettlementAccountUpdate {
+                        id: account.id.clone(),
+                        state,
+                        reason: reason.to_string(),
+                        external_reference: None,
+                    }))
+                    .collect(),
+            })
+            .filter(|participant| !participant.accounts.is_empty())
+            .collect();
+        if participants.is_empty() {
+            None
+        } else {
+            Some(PutSettlement { id: settlement.id, update: SettlementUpdate { participants } })
         }
     }
+}
+
+impl_request! {
+    PutSettlement: SettlementRequest,
+    PUT "/v2/settlements/{}", id;
+    body: SettlementUpdate = |req| req.update.clone();
+    response: Settlement;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::Request;
+
+    #[test]
+    fn get_settlements_escapes_reserved_characters_in_fsp_id() {
+        let filter: SettlementsFilter = serde_json::from_value(serde_json::json!({
+            "participantId": "dfsp&a=b+c %/",
+            "state": "PENDING_SETTLEMENT",
+        })).unwrap();
+        let req = GetSettlements::try_from(filter).unwrap();
+        assert_eq!(
+            req.path_and_query().unwrap(),
+            "/v2/settlements?participantId=dfsp%26a%3Db%2Bc+%25%2F&state=PENDING_SETTLEMENT",
+        );
+    }
 
-    impl From<GetSettlements> for http::Request<hyper::Body> {
-        fn from(req: GetSettlements) -> http::Request<hyper::Body> {
-            use std::collections::HashMap;
-            let mut query_params: HashMap<&str, String> = HashMap::new();
-            if let Some(c) = req.currency { query_params.insert("currency", c.to_string()); }
-            if let Some(pid) = &req.participant_id { query_params.insert("participantId", pid.to_string()); }
-            if let Some(swid) = &req.settlement_window_id { query_params.insert("settlementWindowId", swid.to_string()); }
-            if let Some(st) = &req.state { query_params.insert("state", st.to_string()); }
-            if let Some(from) = req.from_date_time { query_params.insert("fromDateTime", from.to_string()); }
-            if let Some(to) = req.to_date_time { query_params.insert("toDateTime", to.to_string()); }
-            if let Some(sw_from) = req.from_settlement_window_date_time { query_params.insert("fromSettlementWindowDateTime", sw_from.to_string()); }
-            if let Some(sw_to) = req.to_settlement_window_date_time { query_params.insert("toSettlementWindowDateTime", sw_to.to_string()); }
-            // TODO: this assert isn't great, we'd prefer correct by construction, if possible
-            assert!(query_params.len() > 0, "At least one GET /settlements query parameter is required");
-            let query_string = format!(
-                "{}",
-                query_params
-                .iter()
-                .map(|(k, v)|
-                    format!(
-                        "{}={}",
-                        utf8_percent_encode(k, &QUERY_ENCODE_SET),
-                        utf8_percent_encode(v, &QUERY_ENCODE_SET),
-                    )
-                )
-                .format("&")
-            );
-            get(format!("/v2/settlements?{}", query_string).as_str(), &NoBody)
-        }
+    #[test]
+    fn get_settlements_rejects_empty_filter() {
+        assert!(GetSettlements::try_from(SettlementsFilter::default()).is_err());
+    }
+
+    #[test]
+    fn get_settlement_participant_path() {
+        let req = GetSettlementParticipant {
+            id: "3".parse().unwrap(),
+            participant_id: "12".parse().unwrap(),
+        };
+        assert_eq!(req.path_and_query().unwrap(), "/v2/settlements/3/participants/12");
+        assert_eq!(
+            GetSettlement { id: "3".parse().unwrap() }.path_and_query().unwrap(),
+            "/v2/settlements/3",
+        );
+    }
+
+    #[test]
+    fn participant_settlement_has_no_dates() {
+        let settlement: ParticipantSettlement = serde_json::from_value(serde_json::json!({
+            "id": 3,
+            "state": "PENDING_SETTLEMENT",
+            "settlementWindows": [],
+            "participants": [{
+                "id": 12,
+                "accounts": [{
+                    "id": 20,
+                    "reason": "",
+                    "state": "PENDING_SETTLEMENT",
+                    "netSettlementAmount": { "amount": "100", "currency": "USD" },
+                }],
+            }],
+        })).unwrap();
+        assert_eq!(settlement.participants[0].accounts.len(), 1);
+    }
+
+    fn settlement(account_states: &[(u64, &[SettlementState])]) -> Settlement {
+        let mut account_id = 0;
+        serde_json::from_value(serde_json::json!({
+            "id": 3,
+            "state": "PENDING_SETTLEMENT",
+            "createdDate": "2021-06-01T10:00:00.000Z",
+            "changedDate": "2021-06-01T10:00:00.000Z",
+            "settlementWindows": [],
+            "participants": account_states.iter().map(|(participant, states)| serde_json::json!({
+                "id": participant,
+                "accounts": states.iter().map(|state| {
+                    account_id += 1;
+                    serde_json::json!({
+                        "id": account_id,
+                        "reason": "",
+                        "state": state,
+                        "netSettlementAmount": { "amount": 0, "currency": "USD" },
+                    })
+                }).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })).unwrap()
+    }
+
+    #[test]
+    fn get_settlements_in_state() {
+        assert_eq!(
+            GetSettlements::in_state(SettlementState::PsTransfersRecorded).path_and_query().unwrap(),
+            "/v2/settlements?state=PS_TRANSFERS_RECORDED",
+        );
+    }
+
+    #[test]
+    fn advance_moves_each_unsettled_account_to_its_next_state() {
+        use SettlementState::*;
+        let settlement = settlement(&[
+            (1, &[PendingSettlement, Settled]),
+            (2, &[PsTransfersCommitted]),
+            (3, &[Aborted]),
+        ]);
+        let req = PutSettlement::advance(&settlement, "Settlement run").unwrap();
+        assert_eq!(req.path_and_query().unwrap(), "/v2/settlements/3");
+        assert_eq!(serde_json::to_value(req.body()).unwrap(), serde_json::json!({
+            "participants": [
+                {
+                    "id": 1,
+                    "accounts": [{ "id": 1, "state": "PS_TRANSFERS_RECORDED", "reason": "Settlement run" }],
+                },
+                {
+                    "id": 2,
+                    "accounts": [{ "id": 3, "state": "SETTLED", "reason": "Settlement run" }],
+                },
+            ],
+        }));
+    }
+
+    #[test]
+    fn a_settled_or_aborted_settlement_cannot_advance() {
+        use SettlementState::*;
+        assert!(PutSettlement::advance(&settlement(&[(1, &[Settled]), (2, &[Aborted])]), "").is_none());
+        assert!(PutSettlement::advance(&settlement(&[]), "").is_none());
+        assert_eq!(Settling.next(), None);
     }
 }
@@ -1,9 +1,13 @@
 use serde::{Serialize, Deserialize};
+use std::convert::TryFrom;
 use fspiox_api::{Currency, FspId, DateTime};
 use crate::central_ledger::participants::LedgerAccountType;
 use crate::settlement::settlement::SettlementId;
 use derive_more::{Display, FromStr};
 use strum_macros::{ToString, EnumString};
+use crate::request::{NoBody, impl_request};
+use crate::typescript::ts_type_alias;
+use crate::settlement::SettlementRequest;
 
 #[cfg(feature = "typescript_types")]
 use ts_rs::TS;
@@ -27,11 +31,12 @@ pub enum SettlementWindowState {
 // TODO: is this actually u64? It's likely whatever type MySQL uses as an auto-incrementing
 // integer.
 #[cfg_attr(feature = "typescript_types", derive(TS))]
-#[derive(Serialize, Deserialize, Debug, FromStr, Clone, Copy, Display)]
+#[derive(Serialize, Deserialize, Debug, FromStr, Clone, Copy, Display, PartialEq, Eq, Hash)]
 pub struct SettlementWindowId(u64);
 
-// TODO: what.. is.. this? What is the settlement window content id? Is it actually the same as the
-// settlementwindowid?
+// The ID of a row of central-settlement's settlementWindowContent table. A settlement window has
+// one content row per ledger account type and currency that had transfers in the window, so this
+// is not the settlement window ID; each content item belongs to the window it's returned with.
 // Here's the spec: https://github.com/mojaloop/central-settlement/blob/e3c8cf8fc61543d1ab70880765ced23a9e98cb25/src/interface/swagger.json#L1135
 // "integer"
 #[cfg_attr(feature = "typescript_types", derive(TS))]
@@ -42,7 +47,6 @@ pub struct SettlementWindowContentId(u64);
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SettlementWindowContent {
-    // TODO: is id the settlement window ID? Must be, right?
     pub id: SettlementWindowContentId,
     // TODO: not in the spec
     // https://github.com/mojaloop/central-settlement/blob/15d42ce259b3c1c57e81874c40ab5f5fb0981c6e/src/interface/swagger.json#L1134
@@ -53,7 +57,9 @@ pub struct SettlementWindowContent {
     pub state: SettlementWindowState,
     pub ledger_account_type: LedgerAccountType,
     pub currency_id: Currency,
+    #[serde(with = "crate::datetime::lenient")]
     pub created_date: DateTime,
+    #[serde(default, with = "crate::datetime::lenient::option")]
     pub changed_date: Option<DateTime>,
     // TODO: in spec, doesn't seem to be returned
     pub settlement_id: Option<SettlementId>,
@@ -70,13 +76,40 @@ pub struct SettlementWindow {
     pub settlement_window_id: SettlementWindowId,
     pub reason: Option<String>,
     pub state: SettlementWindowState,
+    #[serde(with = "crate::datetime::lenient")]
     pub created_date: DateTime,
+    #[serde(default, with = "crate::datetime::lenient::option")]
     pub changed_date: Option<DateTime>,
     pub content: Option<Vec<SettlementWindowContent>>,
 }
 
+impl SettlementWindow {
+    /// The window's content, one item per ledger account type and currency with transfers in the
+    /// window. Central-settlement omits the content for windows with no transfers.
+    pub fn content(&self) -> &[SettlementWindowContent] {
+        self.content.as_deref().unwrap_or(&[])
+    }
+}
+
 pub type SettlementWindows = Vec<SettlementWindow>;
 
+/// The content of a settlement window, as returned by [`GetSettlementWindowContent`]
+#[cfg_attr(feature = "typescript_types", derive(TS))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementWindowContents {
+    pub settlement_window_id: SettlementWindowId,
+    /// Empty for windows with no transfers, for which central-settlement omits the content
+    #[serde(default, deserialize_with = "deserialize_content")]
+    pub content: Vec<SettlementWindowContent>,
+}
+
+fn deserialize_content<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<SettlementWindowContent>, D::Error> {
+    Option::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
 #[cfg_attr(feature = "typescript_types", derive(TS))]
 #[derive(Serialize, Deserialize, Debug, ToString, EnumString, Clone, Copy)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -117,9 +150,9 @@ pub struct CloseSettlementWindow {
 }
 
 #[cfg_attr(feature = "typescript_types", derive(TS))]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
-pub struct GetSettlementWindows {
+pub struct SettlementWindowsFilter {
     pub currency: Option<Currency>,
     pub participant_id: Option<FspId>,
     pub state: Option<SettlementWindowState>,
@@ -127,61 +160,188 @@ pub struct GetSettlementWindows {
     pub to_date_time: Option<DateTime>,
 }
 
+impl SettlementWindowsFilter {
+    pub fn is_empty(&self) -> bool {
+        self.currency.is_none() &&
+        self.participant_id.is_none() &&
+        self.state.is_none() &&
+        self.from_date_time.is_none() &&
+        self.to_date_time.is_none()
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy)]
+#[error("At least one GET /settlementWindows query parameter is required")]
+pub struct EmptySettlementWindowsFilter;
+
+/// GET /v2/settlementWindows. Central-settlement requires at least one query parameter, so this
+/// can only be constructed from a non-empty [`SettlementWindowsFilter`], with
+/// `GetSettlementWindows::try_from`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "SettlementWindowsFilter", into = "SettlementWindowsFilter")]
+pub struct GetSettlementWindows(SettlementWindowsFilter);
+
+impl GetSettlementWindows {
+    /// The windows in `state`. A filter with a state is never empty, so this can't fail.
+    pub fn in_state(state: SettlementWindowState) -> GetSettlementWindows {
+        GetSettlementWindows(SettlementWindowsFilter {
+            state: Some(state),
+            ..SettlementWindowsFilter::default()
+        })
+    }
+
+    pub fn filter(&self) -> &SettlementWindowsFilter {
+        &self.0
+    }
+}
+
+impl TryFrom<SettlementWindowsFilter> for GetSettlementWindows {
+    type Error = EmptySettlementWindowsFilter;
+
+    fn try_from(filter: SettlementWindowsFilter) -> Result<Self, Self::Error> {
+        if filter.is_empty() {
+            Err(EmptySettlementWindowsFilter)
+        } else {
+            Ok(GetSettlementWindows(filter))
+        }
+    }
+}
+
+impl From<GetSettlementWindows> for SettlementWindowsFilter {
+    fn from(item: GetSettlementWindows) -> Self {
+        item.0
+    }
+}
+
+ts_type_alias!(GetSettlementWindows = "SettlementWindowsFilter", SettlementWindowsFilter);
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GetSettlementWindow {
     pub id: SettlementWindowId,
 }
 
-#[cfg(feature = "hyper")]
-pub mod requests {
-    use crate::settlement::settlement_windows::*;
-    use fspiox_api::clients::NoBody;
-    use crate::clients::requests::{get, post};
+// A PUT, you say? Yes I rather think so. But alas..
+// The response is the window being closed, usually still in the PROCESSING state.
+impl_request! {
+    CloseSettlementWindow: SettlementRequest,
+    POST "/v2/settlementWindows/{}", id;
+    body: SettlementWindowClosurePayload = |req| req.payload.clone();
+    response: SettlementWindow;
+}
 
-    // A PUT, you say? Yes I rather think so. But alas..
-    impl From<CloseSettlementWindow> for http::Request<hyper::Body> {
-        fn from(req: CloseSettlementWindow) -> http::Request<hyper::Body> {
-            post(format!("/v2/settlementWindows/{}", req.id).as_str(), &req.payload)
-        }
+impl_request! {
+    GetSettlementWindow: SettlementRequest,
+    GET "/v2/settlementWindows/{}", id;
+    body: NoBody;
+    response: SettlementWindow;
+}
+
+/// Central-settlement has no endpoint for window content alone, so this is GET
+/// /v2/settlementWindows/{id}, keeping only the content of the window.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetSettlementWindowContent {
+    pub id: SettlementWindowId,
+}
+
+impl_request! {
+    GetSettlementWindowContent: SettlementRequest,
+    GET "/v2/settlementWindows/{}", id;
+    body: NoBody;
+    response: SettlementWindowContents;
+}
+
+impl_request! {
+    GetSettlementWindows: SettlementRequest,
+    GET "/v2/settlementWindows";
+    query: |req| req.filter();
+    body: NoBody;
+    response: SettlementWindows;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::Request;
+
+    #[test]
+    fn get_settlement_windows_escapes_reserved_characters_in_fsp_id() {
+        let filter: SettlementWindowsFilter = serde_json::from_value(serde_json::json!({
+            "participantId": "dfsp&a=b+c",
+            "state": "OPEN",
+        })).unwrap();
+        let req = GetSettlementWindows::try_from(filter).unwrap();
+        assert_eq!(
+            req.path_and_query().unwrap(),
+            "/v2/settlementWindows?participantId=dfsp%26a%3Db%2Bc&state=OPEN",
+        );
     }
 
-    impl From<GetSettlementWindow> for http::Request<hyper::Body> {
-        fn from(req: GetSettlementWindow) -> http::Request<hyper::Body> {
-            get(format!("/v2/settlementWindows/{}", req.id).as_str(), &NoBody)
-        }
+    #[test]
+    fn close_settlement_window_keeps_reserved_characters_in_reason() {
+        let req = CloseSettlementWindow {
+            id: "1".parse().unwrap(),
+            payload: SettlementWindowClosurePayload {
+                state: SettlementWindowCloseState::Closed,
+                reason: "a&b=c+d /%".to_string(),
+            },
+        };
+        assert_eq!(req.path_and_query().unwrap(), "/v2/settlementWindows/1");
+        assert_eq!(
+            req.body_bytes().unwrap(),
+            br#"{"state":"CLOSED","reason":"a&b=c+d /%"}"#.to_vec(),
+        );
     }
 
-    impl From<GetSettlementWindows> for http::Request<hyper::Body> {
-        fn from(req: GetSettlementWindows) -> http::Request<hyper::Body> {
-            use itertools::Itertools;
-            use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
-
-            // https://url.spec.whatwg.org/#query-percent-encode-set
-            const QUERY_ENCODE_SET: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'<').add(b'>').add(b'#');
-
-            use std::collections::HashMap;
-            let mut query_params: HashMap<&str, String> = HashMap::new();
-            if let Some(c) = req.currency { query_params.insert("currency", c.to_string()); }
-            if let Some(id) = &req.participant_id { query_params.insert("participantId", id.to_string()); }
-            if let Some(st) = &req.state { query_params.insert("state", st.to_string()); }
-            if let Some(from) = req.from_date_time { query_params.insert("fromDateTime", from.to_string()); }
-            if let Some(to) = req.to_date_time { query_params.insert("toDateTime", to.to_string()); }
-            // TODO: this assert isn't great, we'd prefer correct by construction, if possible
-            assert!(query_params.len() > 0, "At least one GET /settlementWindows query parameter is required");
-            let query_string = format!(
-                "{}",
-                query_params
-                    .iter()
-                    .map(|(k, v)|
-                        format!(
-                            "{}={}",
-                            utf8_percent_encode(k, &QUERY_ENCODE_SET),
-                            utf8_percent_encode(v, &QUERY_ENCODE_SET),
-                        )
-                    )
-                    .format("&")
-            );
-            get(format!("/v2/settlementWindows?{}", query_string).as_str(), &NoBody)
-        }
+    #[test]
+    fn close_settlement_window_response_is_the_processing_window() {
+        let window: <CloseSettlementWindow as Request>::Response = serde_json::from_value(serde_json::json!({
+            "settlementWindowId": 1,
+            "reason": "Scheduled closure",
+            "state": "PROCESSING",
+            "createdDate": "2021-01-01T01:23:34.000Z",
+            "changedDate": "2021-01-01T05:00:00.000Z",
+        })).unwrap();
+        assert_eq!(window.settlement_window_id, "1".parse().unwrap());
+        assert_eq!(window.state, SettlementWindowState::Processing);
+    }
+
+    #[test]
+    fn get_settlement_window_content_keeps_only_the_content() {
+        let req = GetSettlementWindowContent { id: "4".parse().unwrap() };
+        assert_eq!(req.path_and_query().unwrap(), "/v2/settlementWindows/4");
+        let contents: SettlementWindowContents = serde_json::from_value(serde_json::json!({
+            "settlementWindowId": 4,
+            "reason": "close",
+            "state": "CLOSED",
+            "createdDate": "2021-06-01T10:00:00.000Z",
+            "changedDate": "2021-06-01T11:00:00.000Z",
+            "content": [
+                {
+                    "id": 7,
+                    "state": "CLOSED",
+                    "ledgerAccountType": "POSITION",
+                    "currencyId": "USD",
+                    "createdDate": "2021-06-01T11:00:00.000Z",
+                    "settlementId": null,
+                },
+                {
+                    "id": 8,
+                    "state": "CLOSED",
+                    "ledgerAccountType": "POSITION",
+                    "currencyId": "EUR",
+                    "createdDate": "2021-06-01T11:00:00.000Z",
+                },
+            ],
+        })).unwrap();
+        assert_eq!(contents.content.len(), 2);
+        assert_eq!(contents.content[1].currency_id, Currency::EUR);
+
+        let empty: SettlementWindowContents = serde_json::from_value(serde_json::json!({
+            "settlementWindowId": 5,
+            "reason": null,
+            "state": "OPEN",
+            "createdDate": "2021-06-01T11:00:00.000Z",
+        })).unwrap();
+        assert!(empty.content.is_empty());
     }
 }
@@ -0,0 +1,30 @@
+// ts-rs only derives declarations for types whose serde representation it can see, and declares
+// a newtype as a wrapper rather than as its inner type. Types with hand-written or validated
+// serde implementations are declared with this instead, as a TypeScript type alias, e.g.
+//
+//     ts_type_alias!(SettlementAccountId = "number");
+//     ts_type_alias!(GetSettlements = "SettlementsFilter", SettlementsFilter);
+//
+// where any types following the declaration are those it refers to.
+// TODO: contribute a PR to ts-rs to make this the default implementation for a newtype
+macro_rules! ts_type_alias {
+    ($name:ident = $decl:literal $(, $dep:ty)* $(,)?) => {
+        #[cfg(feature = "typescript_types")]
+        impl ::ts_rs::TS for $name {
+            fn name() -> String {
+                stringify!($name).to_string()
+            }
+
+            fn dependencies() -> Vec<(std::any::TypeId, String)> {
+                vec![$((std::any::TypeId::of::<$dep>(), <$dep as ::ts_rs::TS>::name())),*]
+            }
+
+            fn transparent() -> bool { false }
+
+            fn decl() -> String {
+                format!("type {} = {}", stringify!($name), $decl)
+            }
+        }
+    };
+}
+pub(crate) use ts_type_alias;